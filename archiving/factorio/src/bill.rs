@@ -0,0 +1,160 @@
+// bill of materials, the items needed to build prints,
+// entities by their placing item, items requested into entities like modules, and tiles by their placing item,
+// each counted by quality and summed across books, render as table for reading or json for other tools
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use serde_json::{Map, Value};
+
+use crate::blueprint_library::{Blueprint, Print};
+
+// quality of entity or item without quality,
+// also for everything from library file, where binary layout of quality is unknown
+const NORMAL_QUALITY: &str = "normal";
+// vanilla qualities in game order, other qualities are after them by name
+const QUALITY_ORDER: [&str; 5] = ["normal", "uncommon", "rare", "epic", "legendary"];
+
+// (item name, quality) to count
+pub type ItemCounts<'a> = BTreeMap<(&'a str, &'a str), usize>;
+
+#[derive(Default)]
+pub struct BillOfMaterials<'a> {
+    pub entities: ItemCounts<'a>,
+    // items requested into entities, like modules, fuel and robots
+    pub items: ItemCounts<'a>,
+    pub tiles: ItemCounts<'a>,
+}
+
+impl<'a> BillOfMaterials<'a> {
+    // planners have nothing to build
+    pub fn add_print(&mut self, print: &'a Print) {
+        match print {
+            Print::Blueprint(blueprint) => self.add_blueprint(blueprint),
+            Print::BlueprintBook(book) => for (_, print) in &book.prints {
+                self.add_print(print);
+            },
+            Print::UpgradePlan(_) | Print::DeconstructionPlan(_) => {},
+        }
+    }
+
+    pub fn add_blueprint(&mut self, blueprint: &'a Blueprint) {
+        for entity in &blueprint.entities {
+            if let Some((item_name, count)) = entity.kind.placing_item() {
+                let quality = entity.quality.as_deref().unwrap_or(NORMAL_QUALITY);
+                *self.entities.entry((item_name, quality)).or_default() += count;
+            }
+            for (item_name, quality, count) in &entity.items {
+                *self.items.entry((item_name, quality.as_deref().unwrap_or(NORMAL_QUALITY))).or_default() += count;
+            }
+        }
+        // tiles have no quality
+        for (tile_name, _) in &blueprint.tiles {
+            *self.tiles.entry((tile_item(tile_name), NORMAL_QUALITY)).or_default() += 1;
+        }
+    }
+
+    fn sections(&self) -> [(&'static str, &ItemCounts<'a>); 3] {
+        [("entities", &self.entities), ("items", &self.items), ("tiles", &self.tiles)]
+    }
+}
+
+// most tiles are placed by item of same name, both direction of hazard concrete are placed by same item
+fn tile_item(tile_name: &str) -> &str {
+    match tile_name {
+        "stone-path" => "stone-brick",
+        "hazard-concrete-left" | "hazard-concrete-right" => "hazard-concrete",
+        "refined-hazard-concrete-left" | "refined-hazard-concrete-right" => "refined-hazard-concrete",
+        other => other,
+    }
+}
+
+fn sort_qualities<'a>(counts: &ItemCounts<'a>) -> Vec<&'a str> {
+    let mut qualities = counts.keys().map(|(_, quality)| *quality).collect::<Vec<_>>();
+    qualities.sort_by_key(|quality| (QUALITY_ORDER.iter().position(|q| q == quality).unwrap_or(QUALITY_ORDER.len()), *quality));
+    qualities.dedup();
+    qualities
+}
+
+// like
+// entities                  normal  legendary     total
+//   assembling-machine-3         2          1         3
+// one column for each quality in the section, empty for 0
+pub fn render_table(bill: &BillOfMaterials) -> String {
+    let mut result = String::new();
+    for (section, counts) in bill.sections() {
+        if counts.is_empty() { continue; }
+        let qualities = sort_qualities(counts);
+        let name_width = counts.keys().map(|(name, _)| name.len() + 2).chain([section.len()]).max().unwrap_or(0);
+
+        write!(result, "{section:name_width$}").unwrap();
+        for quality in &qualities {
+            write!(result, "  {quality:>8}").unwrap();
+        }
+        writeln!(result, "  {:>8}", "total").unwrap();
+
+        let mut names = counts.keys().map(|(name, _)| *name).collect::<Vec<_>>();
+        names.dedup();
+        for name in names {
+            write!(result, "  {name:width$}", width = name_width - 2).unwrap();
+            for quality in &qualities {
+                match counts.get(&(name, *quality)) {
+                    Some(count) => write!(result, "  {count:>8}").unwrap(),
+                    None => write!(result, "  {:>8}", "").unwrap(),
+                }
+            }
+            let total = qualities.iter().filter_map(|quality| counts.get(&(name, *quality))).sum::<usize>();
+            writeln!(result, "  {total:>8}").unwrap();
+        }
+    }
+    result
+}
+
+// like { "entities": { "assembling-machine-3": { "normal": 2, "legendary": 1 } }, "items": {}, "tiles": {} }
+pub fn render_json(bill: &BillOfMaterials) -> Value {
+    let mut result = Map::new();
+    for (section, counts) in bill.sections() {
+        let mut items = Map::new();
+        for ((name, quality), count) in counts {
+            let qualities = items.entry(name.to_string()).or_insert_with(|| Value::Object(Map::new()));
+            qualities[*quality] = (*count).into();
+        }
+        result.insert(section.into(), items.into());
+    }
+    result.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blueprint_library::*;
+    use crate::entity::*;
+    use super::*;
+
+    #[test]
+    fn book_bill() {
+        let mut blueprint = Blueprint::new("rails", (2, 0, 28, 1));
+        blueprint.add_entity(EntityKind::Rail(Rail::from_name("straight-rail", 0).unwrap()), (1.0, 1.0));
+        blueprint.add_entity(EntityKind::Rail(Rail::from_name("elevated-curved-rail-a", 0).unwrap()), (5.0, 1.0));
+        blueprint.add_entity(EntityKind::Rail(Rail::from_name("rail-support", 0).unwrap()), (9.0, 1.0));
+        blueprint.add_entity(EntityKind::Accumulator(Accumulator{ circuit_connections: None, output_signal: None }), (12.0, 1.0))
+            .set_quality("legendary");
+        blueprint.add_entity(EntityKind::CraftingMachine(CraftingMachine{ machine: CraftingMachineType::AssemblingMachine3,
+            direction: 0, mirrored: false, recipe: None, recipe_quality: None }), (15.5, 1.5))
+            .add_quality_item("speed-module", "rare", 2).add_item("speed-module", 2);
+        blueprint.add_tile("hazard-concrete-left", (0, 0)).add_tile("hazard-concrete-right", (1, 0)).add_tile("stone-path", (2, 0));
+
+        let mut book = BlueprintBook::new("book");
+        book.add_print(Print::Blueprint(blueprint));
+        book.add_print(Print::BlueprintBook(BlueprintBook::new("empty")));
+        let book = Print::BlueprintBook(book);
+        let mut bill = BillOfMaterials::default();
+        bill.add_print(&book);
+        bill.add_print(&book);
+
+        assert_eq!(bill.entities, BTreeMap::from([(("rail", "normal"), 8), (("rail-support", "normal"), 2),
+            (("accumulator", "legendary"), 2), (("assembling-machine-3", "normal"), 2)]));
+        assert_eq!(bill.items, BTreeMap::from([(("speed-module", "rare"), 4), (("speed-module", "normal"), 4)]));
+        assert_eq!(bill.tiles, BTreeMap::from([(("hazard-concrete", "normal"), 4), (("stone-brick", "normal"), 2)]));
+        assert_eq!(render_table(&bill).lines().nth(1), Some("  accumulator                            2         2"));
+        assert_eq!(render_json(&bill)["items"]["speed-module"], serde_json::json!({ "normal": 4, "rare": 4 }));
+    }
+}
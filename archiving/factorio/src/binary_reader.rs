@@ -1,7 +1,12 @@
 // basic binary reader from binary buffer
 // offerring a little more high level functions like read as u32 and read string
+// optionally record every read for reverse engineering, see trace.rs
 
-use anyhow::bail;
+use std::borrow::Cow;
+use std::fmt::Display;
+
+use crate::error::{ParseError, ParseErrorKind, ParseResult};
+use crate::trace::{PathSegment, TraceRecord};
 
 // NOTE this is borrowing the buffer,
 // because the returned &str is borrowing the buffer,
@@ -10,12 +15,25 @@ pub struct Reader<'a> {
     base: &'a [u8],
     // // why is std::io::Cursor using u64 as position
     position: usize,
+    // semantic path of current reading position, maintained by caller
+    path: Vec<PathSegment>,
+    // name of next read in current path, consumed by next read
+    field: Option<&'static str>,
+    // offset and name of last read, for error about the value just read
+    last_offset: usize,
+    last_field: Option<&'static str>,
+    // None for not tracing
+    trace: Option<Vec<TraceRecord<'a>>>,
 }
 
 // construct
 impl<'a> Reader<'a> {
     pub fn new(base: &'a [u8]) -> Self {
-        Self{ base, position: 0 }
+        Self{ base, position: 0, path: Vec::new(), field: None, last_offset: 0, last_field: None, trace: None }
+    }
+    // record every read, get records by take_trace
+    pub fn with_trace(base: &'a [u8]) -> Self {
+        Self{ trace: Some(Vec::new()), ..Self::new(base) }
     }
 }
 
@@ -25,102 +43,199 @@ impl<'a> Reader<'a> {
     pub fn position(&self) -> usize {
         self.position
     }
-    #[allow(dead_code)]
-    pub fn set_position(&mut self, position: usize) -> anyhow::Result<()> {
-        if position >= self.base.len() { bail!("index out of range") }
+    // position == length is allowed, that is at the end of buffer
+    pub fn set_position(&mut self, position: usize) -> ParseResult<()> {
+        if position > self.base.len() { return Err(self.out_of_range(position)); }
         self.position = position;
         Ok(())
     }
-    pub fn skip(&mut self, length: usize) -> anyhow::Result<()> {
-        if self.position + length >= self.base.len() { bail!("index out of range") }
-        self.position += length;
+    // get original bytes of a range, for things not understood but need to keep
+    pub fn slice(&self, start: usize, end: usize) -> ParseResult<&'a [u8]> {
+        if start > end || end > self.base.len() { return Err(self.out_of_range(end)); }
+        Ok(&self.base[start..end])
+    }
+    #[allow(dead_code)]
+    pub fn skip(&mut self, length: usize) -> ParseResult<()> {
+        let offset = self.position;
+        let raw = self.take(length)?;
+        self.record(offset, raw, || format!("skip {length}"));
         Ok(())
     }
+
+    // get bytes and advance, all reads are bound checked here
+    // NOTE compare by subtract from length, position + length may overflow for corrupted length
+    fn take(&mut self, length: usize) -> ParseResult<&'a [u8]> {
+        if length > self.base.len() - self.position {
+            let error = self.out_of_range(self.position.saturating_add(length));
+            self.field = None;
+            return Err(error);
+        }
+        let result = &self.base[self.position..self.position + length];
+        self.position += length;
+        Ok(result)
+    }
+}
+
+// path and trace
+impl<'a> Reader<'a> {
+
+    pub fn enter(&mut self, name: &'static str) {
+        self.path.push(PathSegment::Field(name));
+    }
+    pub fn enter_index(&mut self, name: &'static str, index: usize) {
+        self.path.push(PathSegment::Index(name, index));
+    }
+    pub fn leave(&mut self) {
+        self.path.pop();
+    }
+    // use with restore_path when leave may be skipped by error
+    pub fn path_depth(&self) -> usize {
+        self.path.len()
+    }
+    pub fn restore_path(&mut self, depth: usize) {
+        self.path.truncate(depth);
+        self.field = None;
+    }
+    // name the next read, like self.base.field("label").read_str()
+    pub fn field(&mut self, name: &'static str) -> &mut Self {
+        self.field = Some(name);
+        self
+    }
+
+    pub fn take_trace(&mut self) -> Vec<TraceRecord<'a>> {
+        self.trace.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    // value is formatted only when tracing
+    fn record<T: Display>(&mut self, offset: usize, raw: &'a [u8], value: impl FnOnce() -> T) {
+        let field = self.field.take();
+        self.last_offset = offset;
+        self.last_field = field;
+        if let Some(trace) = &mut self.trace {
+            trace.push(TraceRecord{ offset, raw, value: value().to_string(), path: format_path(&self.path, field) });
+        }
+    }
+}
+
+fn format_path(path: &[PathSegment], field: Option<&'static str>) -> String {
+    let mut result = crate::trace::format_path(path);
+    if let Some(field) = field {
+        if !result.is_empty() { result.push('.'); }
+        result.push_str(field);
+    }
+    result
+}
+
+// error
+impl<'a> Reader<'a> {
+
+    // error about the value just read
+    pub fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError{ offset: self.last_offset, path: format_path(&self.path, self.last_field), kind }
+    }
+    // error about something started at offset in current path, like an entity
+    pub fn error_at(&self, offset: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError{ offset, path: format_path(&self.path, None), kind }
+    }
+    // the byte just read is not valid for what it means, like "print type"
+    pub fn unexpected(&self, expected: &'static str, actual: u8) -> ParseError {
+        self.error(ParseErrorKind::UnexpectedByte{ expected: Cow::Borrowed(expected), actual })
+    }
+    // before reading, so use current position and pending field
+    fn out_of_range(&self, end: usize) -> ParseError {
+        ParseError{ offset: self.position, path: format_path(&self.path, self.field), kind: ParseErrorKind::OutOfRange{ end, length: self.base.len() } }
+    }
 }
 
 // read
 impl<'a> Reader<'a> {
 
-    pub fn read_u8(&mut self) -> anyhow::Result<u8> {
-        if self.position == self.base.len() { bail!("index out of range"); }
-        let result = self.base[self.position];
-        self.position += 1;
+    pub fn read_u8(&mut self) -> ParseResult<u8> {
+        let offset = self.position;
+        let raw = self.take(1)?;
+        let result = raw[0];
+        self.record(offset, raw, || result);
         Ok(result)
     }
     // bool is always 0/1, no other value
-    pub fn read_bool(&mut self) -> anyhow::Result<bool> {
-        if self.position == self.base.len() { bail!("index out of range"); }
-        let value = self.base[self.position];
-        // capture backtrace: set RUST_BACKTRACE=1
-        if value != 0 && value != 1 { bail!("0x{:x}: expect bool, meet {}, {:?}", self.position, value, std::backtrace::Backtrace::capture()); }
-        self.position += 1;
+    pub fn read_bool(&mut self) -> ParseResult<bool> {
+        let offset = self.position;
+        let raw = self.take(1)?;
+        let value = raw[0];
+        self.record(offset, raw, || value != 0);
+        if value != 0 && value != 1 { return Err(self.unexpected("bool", value)); }
         Ok(value != 0)
     }
 
-    pub fn read_u16(&mut self) -> anyhow::Result<u16> {
-        if self.position >= self.base.len() - 1 { bail!("index out of range"); }
-        let result = u16::from_le_bytes([self.base[self.position], self.base[self.position + 1]]);
-        self.position += 2;
+    pub fn read_u16(&mut self) -> ParseResult<u16> {
+        let offset = self.position;
+        let raw = self.take(2)?;
+        let result = u16::from_le_bytes([raw[0], raw[1]]);
+        self.record(offset, raw, || result);
         Ok(result)
     }
-    pub fn read_i16(&mut self) -> anyhow::Result<i16> {
-        if self.position >= self.base.len() - 1 { bail!("index out of range"); }
-        let result = i16::from_le_bytes([self.base[self.position], self.base[self.position + 1]]);
-        self.position += 2;
+    pub fn read_i16(&mut self) -> ParseResult<i16> {
+        let offset = self.position;
+        let raw = self.take(2)?;
+        let result = i16::from_le_bytes([raw[0], raw[1]]);
+        self.record(offset, raw, || result);
         Ok(result)
     }
 
-    pub fn read_u32(&mut self) -> anyhow::Result<u32> {
-        if self.position >= self.base.len() - 3 { bail!("index out of range"); }
-        let result = u32::from_le_bytes([
-            self.base[self.position],
-            self.base[self.position + 1],
-            self.base[self.position + 2],
-            self.base[self.position + 3],
-        ]);
-        self.position += 4;
+    pub fn read_u32(&mut self) -> ParseResult<u32> {
+        let offset = self.position;
+        let raw = self.take(4)?;
+        let result = u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]);
+        self.record(offset, raw, || result);
         Ok(result)
     }
-    pub fn read_i32(&mut self) -> anyhow::Result<i32> {
-        if self.position >= self.base.len() - 3 { bail!("index out of range"); }
-        let result = i32::from_le_bytes([
-            self.base[self.position],
-            self.base[self.position + 1],
-            self.base[self.position + 2],
-            self.base[self.position + 3],
-        ]);
-        self.position += 4;
+    pub fn read_i32(&mut self) -> ParseResult<i32> {
+        let offset = self.position;
+        let raw = self.take(4)?;
+        let result = i32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]);
+        self.record(offset, raw, || result);
         Ok(result)
     }
 }
 
-// some advance methods
+// some advance methods, each is one record in trace
 impl<'a> Reader<'a> {
 
     // variable length, if not large use one byte, or else allow uin32 length
-    pub fn read_length(&mut self) -> anyhow::Result<usize> {
-        let maybe_length = self.read_u8()?;
-        Ok(if maybe_length == 0xFF { self.read_u32()? as usize } else { maybe_length as usize })
+    pub fn read_length(&mut self) -> ParseResult<usize> {
+        let offset = self.position;
+        let result = self.take_length()?;
+        self.record(offset, &self.base[offset..self.position], || result);
+        Ok(result)
+    }
+    fn take_length(&mut self) -> ParseResult<usize> {
+        let maybe_length = self.take(1)?[0];
+        Ok(if maybe_length == 0xFF {
+            let raw = self.take(4)?;
+            u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]]) as usize
+        } else { maybe_length as usize })
     }
     // string starts with length, no null terminate
     // the returned lifetime need to be explicit, or else it implicitly follow &'self mut self lifetime
-    pub fn read_str(&mut self) -> anyhow::Result<&'a str> {
-        let length = self.read_length()?;
-        let result = std::str::from_utf8(&self.base[self.position..self.position + length])?;
-        self.position += length;
+    pub fn read_str(&mut self) -> ParseResult<&'a str> {
+        let offset = self.position;
+        let length = self.take_length()?;
+        let result = std::str::from_utf8(self.take(length)?).map_err(|error| ParseError{
+            offset, path: format_path(&self.path, self.field.take()), kind: ParseErrorKind::InvalidUtf8(error) })?;
+        self.record(offset, &self.base[offset..self.position], || format!("{result:?}"));
         Ok(result)
     }
 
-    pub fn expect(&mut self, expect_byte: u8) -> anyhow::Result<()> {
+    pub fn expect(&mut self, expect_byte: u8) -> ParseResult<()> {
         let actual = self.read_u8()?;
         if actual != expect_byte {
-            bail!("0x{:x}: expect 0x{expect_byte:x} actual 0x{actual:x}", self.position - 1);
+            return Err(self.error(ParseErrorKind::UnexpectedByte{ expected: Cow::Owned(format!("0x{expect_byte:x}")), actual }));
         }
         Ok(())
     }
 
     // the if tile read_u8 else read_u16 seems widely used
-    pub fn read_u16_unless_then_u8(&mut self, condition: bool) -> anyhow::Result<usize> {
+    pub fn read_u16_unless_then_u8(&mut self, condition: bool) -> ParseResult<usize> {
         Ok(if condition { self.read_u8()? as usize } else { self.read_u16()? as usize })
     }
 }
@@ -0,0 +1,69 @@
+// basic binary writer to binary buffer, the counterpart of binary_reader
+// every read function have a write function with same layout
+
+pub struct Writer {
+    base: Vec<u8>,
+}
+
+// construct
+impl Writer {
+    pub fn new() -> Self {
+        Self{ base: Vec::new() }
+    }
+    pub fn into_inner(self) -> Vec<u8> {
+        self.base
+    }
+}
+
+// write
+impl Writer {
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.base.push(value);
+    }
+    pub fn write_bool(&mut self, value: bool) {
+        self.base.push(value as u8);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.base.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn write_i16(&mut self, value: i16) {
+        self.base.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.base.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn write_i32(&mut self, value: i32) {
+        self.base.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+// some advance methods
+impl Writer {
+
+    // variable length, if less than 0xFF use one byte, or else 0xFF and uint32 length
+    pub fn write_length(&mut self, length: usize) {
+        if length < 0xFF {
+            self.write_u8(length as u8);
+        } else {
+            self.write_u8(0xFF);
+            self.write_u32(length as u32);
+        }
+    }
+    // string starts with length, no null terminate
+    pub fn write_str(&mut self, value: &str) {
+        self.write_length(value.len());
+        self.base.extend_from_slice(value.as_bytes());
+    }
+    // for things not understood but kept, like skipped prints
+    pub fn write_bytes(&mut self, value: &[u8]) {
+        self.base.extend_from_slice(value);
+    }
+
+    // the if tile write_u8 else write_u16
+    pub fn write_u16_unless_then_u8(&mut self, condition: bool, value: usize) {
+        if condition { self.write_u8(value as u8) } else { self.write_u16(value as u16) }
+    }
+}
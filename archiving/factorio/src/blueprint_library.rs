@@ -1,81 +1,714 @@
 
 // the structured storage for blueprint library
+// strings are Cow, borrow the buffer when parsed, owned when edited or when into_owned,
+// an owned BlueprintLibrary<'static> can be used anywhere the borrowed one is expected
 
+use std::borrow::Cow;
 use std::fmt;
 use chrono::{DateTime, Utc};
+use crate::name::Names;
+
+// entity kinds are in their own file, but used as part of this model
+pub use crate::entity::*;
 
-// still borrow the buffer
 pub struct BlueprintLibrary<'a> {
     pub file_version: Version,
+    // (mod name, migration file)[]
+    pub migrations: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub names: Names<'a>,
+    // the byte after names, not known what it is, kept for writing back
+    pub mysterious_byte: u8,
+    pub generation_counter: u32,
     pub file_timestamp: DateTime<Utc>,
-    pub prints: Vec<Print<'a>>,
+    // include inactive slots
+    pub print_count: usize,
+    // (slot index, print)[], inactive slots are not included
+    pub prints: Vec<(usize, Print<'a>)>,
+    // prints not understood by parser
+    pub skipped: Vec<SkippedPrint<'a>>,
 }
 
 pub type Version = (u16, u16, u16, u16);
+// tile name and position, position is the top left corner of the tile
+pub type Tile<'a> = (Cow<'a, str>, (i32, i32));
+
+// binary layout generation, the ATTENTION NOT SAME places dispatch on this,
+// decided by library file version, prints inside use same layout as file, not their own version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layout {
+    // 1.1, 1.0 not tested
+    V1,
+    // 2.0 and space age
+    V2,
+}
+
+impl Layout {
+    pub fn from_version(version: Version) -> Option<Self> {
+        match version.0 {
+            1 => Some(Self::V1),
+            2 => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    // name count and index of this prototype in global names is u8 instead of u16
+    pub fn short_name_index(self, prototype_name: &str) -> bool {
+        match self {
+            Self::V1 => prototype_name == "tile",
+            Self::V2 => prototype_name == "quality",
+        }
+    }
+}
+
+impl<'a> BlueprintLibrary<'a> {
+    // no longer borrow the buffer, not used by main for now
+    #[allow(dead_code)]
+    pub fn into_owned(self) -> BlueprintLibrary<'static> {
+        BlueprintLibrary{
+            file_version: self.file_version,
+            migrations: self.migrations.into_iter().map(|(m, f)| (owned(m), owned(f))).collect(),
+            names: self.names.into_owned(),
+            mysterious_byte: self.mysterious_byte,
+            generation_counter: self.generation_counter,
+            file_timestamp: self.file_timestamp,
+            print_count: self.print_count,
+            prints: self.prints.into_iter().map(|(index, print)| (index, print.into_owned())).collect(),
+            skipped: self.skipped.into_iter().map(SkippedPrint::into_owned).collect(),
+        }
+    }
+}
+
+pub(crate) fn owned(value: Cow<str>) -> Cow<'static, str> {
+    Cow::Owned(value.into_owned())
+}
 
 // should be no need to pretty print
 impl<'a> fmt::Debug for BlueprintLibrary<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "file version {:?}", self.file_version)?;
         writeln!(f, "file timestamp {}", self.file_timestamp)?;
-        for print in &self.prints {
-            write!(f, "{:?}", print)?;
+        for (index, print) in &self.prints {
+            write!(f, "#{index} {:?}", print)?;
+        }
+        for skipped in &self.skipped {
+            writeln!(f, "{:?}", skipped)?;
         }
         Ok(())
     }
 }
 
+// print is skipped by content size when content is not understood
+pub struct SkippedPrint<'a> {
+    // slot indexes from library root
+    pub slot_path: Vec<usize>,
+    pub label: Cow<'a, str>,
+    pub reason: String,
+    // the complete print slot including active flag, written back as is
+    pub raw: Cow<'a, [u8]>,
+}
+
+impl<'a> SkippedPrint<'a> {
+    pub fn into_owned(self) -> SkippedPrint<'static> {
+        SkippedPrint{ slot_path: self.slot_path, label: owned(self.label), reason: self.reason, raw: Cow::Owned(self.raw.into_owned()) }
+    }
+}
+
+impl<'a> fmt::Debug for SkippedPrint<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "skipped {:?} {}: {}", self.slot_path, self.label, self.reason)
+    }
+}
+
 pub struct Blueprint<'a> {
+    // what is generation? kept for writing back
+    pub generation: u32,
+    pub label: Cow<'a, str>,
     pub version: Version,
-    pub description: &'a str,
+    // (mod name, migration file)[]
+    pub migrations: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub description: Cow<'a, str>,
+    // up to 4 icons, None for empty slot
+    pub icons: Vec<Option<Signal<'a>>>,
     pub snap_to_grid: Option<SnapToGrid>,
     pub entities: Vec<BlueprintEntity<'a>>,
+    pub tiles: Vec<Tile<'a>>,
+    // ATTENTION NOT SAME 2/SA 2.0 wires are listed in blueprint, 1.1 wires are in entities, see collect_wires
+    pub wires: Vec<Wire>,
+    // NEW in 2.0, parametrised blueprint
+    pub parameters: Vec<BlueprintParameter<'a>>,
+}
+
+impl<'a> Blueprint<'a> {
+
+    // builders for prints made in code, only used by tests for now,
+    // empty blueprint, version is the game version
+    #[allow(dead_code)]
+    pub fn new(label: impl Into<Cow<'a, str>>, version: Version) -> Self {
+        Self{ generation: 0, label: label.into(), version, migrations: Vec::new(),
+            description: Cow::Borrowed(""), icons: Vec::new(), snap_to_grid: None, entities: Vec::new(), tiles: Vec::new(),
+            wires: Vec::new(), parameters: Vec::new() }
+    }
+    #[allow(dead_code)]
+    pub fn set_label(&mut self, label: impl Into<Cow<'a, str>>) -> &mut Self {
+        self.label = label.into();
+        self
+    }
+    #[allow(dead_code)]
+    pub fn set_description(&mut self, description: impl Into<Cow<'a, str>>) -> &mut Self {
+        self.description = description.into();
+        self
+    }
+    // absolute is the position relative to map coordinate, None for not absolute snapping
+    #[allow(dead_code)]
+    pub fn set_snap_to_grid(&mut self, size: (u32, u32), absolute: Option<(u32, u32)>) -> &mut Self {
+        self.snap_to_grid = Some(SnapToGrid{ size, absolute: absolute.unwrap_or((0, 0)) });
+        self
+    }
+    // entity id is 0, set it if referenced by others
+    #[allow(dead_code)]
+    pub fn add_entity(&mut self, kind: EntityKind<'a>, position: (f64, f64)) -> &mut BlueprintEntity<'a> {
+        self.entities.push(BlueprintEntity{ kind, position, entity_id: 0, quality: None, items: Vec::new(), tags: Vec::new() });
+        self.entities.last_mut().unwrap()
+    }
+    #[allow(dead_code)]
+    pub fn add_tile(&mut self, name: impl Into<Cow<'a, str>>, position: (i32, i32)) -> &mut Self {
+        self.tiles.push((name.into(), position));
+        self
+    }
+
+    pub fn into_owned(self) -> Blueprint<'static> {
+        Blueprint{
+            generation: self.generation,
+            label: owned(self.label),
+            version: self.version,
+            migrations: self.migrations.into_iter().map(|(m, f)| (owned(m), owned(f))).collect(),
+            description: owned(self.description),
+            icons: self.icons.into_iter().map(|icon| icon.map(Signal::into_owned)).collect(),
+            snap_to_grid: self.snap_to_grid,
+            entities: self.entities.into_iter().map(BlueprintEntity::into_owned).collect(),
+            tiles: self.tiles.into_iter().map(|(name, position)| (owned(name), position)).collect(),
+            wires: self.wires,
+            parameters: self.parameters.into_iter().map(BlueprintParameter::into_owned).collect(),
+        }
+    }
+
+    // blueprint json format's entity number is 1 based index in entity list,
+    // while entity id is only assigned to entities referenced by others, like circuit connections
+    pub fn entity_number(&self, entity_id: usize) -> Option<usize> {
+        if entity_id == 0 { return None; }
+        self.entities.iter().position(|e| e.entity_id == entity_id).map(|index| index + 1)
+    }
+
+    // 2.0 wire list and wires converted from 1.1 entity circuit and copper connections,
+    // 1.1 connection is in both connected entities, only kept once
+    pub fn collect_wires(&self) -> Vec<Wire> {
+        let mut wires = self.wires.clone();
+        for (index, entity) in self.entities.iter().enumerate() {
+            for (circuit_id, connections) in entity.kind.circuit_connections() {
+                for (red, targets) in [(true, &connections.red), (false, &connections.green)] {
+                    for (target_id, target_circuit_id) in targets {
+                        let (Some(from), Some(to), Some(target_number)) = (WireConnector::from_circuit(red, circuit_id),
+                            WireConnector::from_circuit(red, *target_circuit_id), self.entity_number(*target_id)) else { continue };
+                        let wire = Wire::new((index, from), (target_number - 1, to));
+                        if !wires.contains(&wire) {
+                            wires.push(wire);
+                        }
+                    }
+                }
+            }
+            for (connector, targets) in entity.kind.copper_connections() {
+                for target_number in targets.iter().filter_map(|target_id| self.entity_number(*target_id)) {
+                    let wire = Wire::new((index, connector), (target_number - 1, WireConnector::Copper));
+                    if !wires.contains(&wire) {
+                        wires.push(wire);
+                    }
+                }
+            }
+        }
+        wires
+    }
+
+    // electric poles grouped by copper wires, each group is entity index[] in entity list order,
+    // power switch connects its two sides only when it is on
+    pub fn electric_networks(&self) -> Vec<Vec<usize>> {
+        // right side of power switch is node entity count + entity index
+        let mut parents = (0..self.entities.len() * 2).collect::<Vec<_>>();
+        fn find(parents: &mut [usize], node: usize) -> usize {
+            let mut root = node;
+            while parents[root] != root {
+                root = parents[root];
+            }
+            parents[node] = root;
+            root
+        }
+        let node = |(index, connector): (usize, WireConnector)|
+            if connector == WireConnector::RightCopper { self.entities.len() + index } else { index };
+        // wires edited or imported from json may point to entity not in list, they connect nothing
+        let copper_wires = self.collect_wires().into_iter()
+            .filter(|wire| wire.from.0 < self.entities.len() && wire.to.0 < self.entities.len())
+            .filter(|wire| matches!(wire.from.1, WireConnector::Copper | WireConnector::RightCopper));
+        let switches = self.entities.iter().enumerate().filter_map(|(index, entity)| match &entity.kind {
+            EntityKind::PowerSwitch(switch) if switch.switch_state => Some((index, self.entities.len() + index)),
+            _ => None,
+        });
+        for (from, to) in copper_wires.map(|wire| (node(wire.from), node(wire.to))).chain(switches) {
+            let (from, to) = (find(&mut parents, from), find(&mut parents, to));
+            parents[from] = to;
+        }
+
+        let mut networks: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (index, entity) in self.entities.iter().enumerate() {
+            if !matches!(entity.kind, EntityKind::ElectricPole(_)) { continue; }
+            let root = find(&mut parents, index);
+            match networks.iter_mut().find(|(network_root, _)| *network_root == root) {
+                Some((_, poles)) => poles.push(index),
+                None => networks.push((root, vec![index])),
+            }
+        }
+        networks.into_iter().map(|(_, poles)| poles).collect()
+    }
 }
 
 impl<'a> fmt::Debug for Blueprint<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "blueprint {:?}", self.version)?;
+        writeln!(f, "blueprint {} {:?}", self.label, self.version)?;
         if !self.description.is_empty() {
-            write!(f, "  description: {}", self.description)?;
+            writeln!(f, "  description: {}", self.description)?;
+        }
+        for icon in self.icons.iter().flatten() {
+            writeln!(f, "  icon {:?}", icon)?;
         }
         if let Some(snap) = &self.snap_to_grid {
-            write!(f, "  snap to grid {:?}, {:?}", snap.size, snap.absolute)?;
+            writeln!(f, "  snap to grid {:?}, {:?}", snap.size, snap.absolute)?;
         }
         for entity in &self.entities {
             write!(f, "{:?}", entity)?;
         }
+        for (name, position) in &self.tiles {
+            writeln!(f, "  tile {} {:?}", name, position)?;
+        }
+        for wire in &self.wires {
+            writeln!(f, "  wire {:?}", wire)?;
+        }
+        for parameter in &self.parameters {
+            write!(f, "{:?}", parameter)?;
+        }
         Ok(())
     }
 }
 
-pub struct BlueprintBook {}
+pub struct BlueprintParameter<'a> {
+    pub name: Cow<'a, str>,
+    // kept as is when placing the blueprint, not asked
+    pub not_parametrised: bool,
+    pub kind: ParameterKind<'a>,
+}
 
-impl fmt::Debug for BlueprintBook {
-    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+pub enum ParameterKind<'a> {
+    // id is parameter signal like parameter-0, replaced by item, fluid or recipe when placing
+    Id{
+        id: Cow<'a, str>,
+        quality_condition: Option<QualityCondition<'a>>,
+        // id of the recipe parameter this is ingredient of, then this is not asked
+        ingredient_of: Option<Cow<'a, str>>,
+    },
+    // number is the constant in entities to be replaced, kept as text like json format
+    Number{
+        number: Cow<'a, str>,
+        // name used by formula of other number parameters
+        variable: Option<Cow<'a, str>>,
+        formula: Option<Cow<'a, str>>,
+        // calculated by formula, then this is not asked
+        dependent: bool,
+    },
+}
+
+impl<'a> BlueprintParameter<'a> {
+    pub fn into_owned(self) -> BlueprintParameter<'static> {
+        BlueprintParameter{
+            name: owned(self.name),
+            not_parametrised: self.not_parametrised,
+            kind: match self.kind {
+                ParameterKind::Id{ id, quality_condition, ingredient_of } => ParameterKind::Id{
+                    id: owned(id),
+                    quality_condition: quality_condition.map(QualityCondition::into_owned),
+                    ingredient_of: ingredient_of.map(owned),
+                },
+                ParameterKind::Number{ number, variable, formula, dependent } => ParameterKind::Number{
+                    number: owned(number),
+                    variable: variable.map(owned),
+                    formula: formula.map(owned),
+                    dependent,
+                },
+            },
+        }
+    }
+}
+
+impl<'a> fmt::Debug for BlueprintParameter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ParameterKind::Id{ id, quality_condition, ingredient_of } => {
+                write!(f, "  parameter {} id {}", self.name, id)?;
+                if let Some(quality) = quality_condition {
+                    write!(f, " quality {:?}", quality)?;
+                }
+                if let Some(recipe) = ingredient_of {
+                    write!(f, " ingredient of {}", recipe)?;
+                }
+            },
+            ParameterKind::Number{ number, variable, formula, dependent } => {
+                write!(f, "  parameter {} number {}", self.name, number)?;
+                if let Some(variable) = variable {
+                    write!(f, " variable {}", variable)?;
+                }
+                if let Some(formula) = formula {
+                    write!(f, " formula {}", formula)?;
+                }
+                if *dependent {
+                    write!(f, " (dependent)")?;
+                }
+            },
+        }
+        writeln!(f, "{}", if self.not_parametrised { " (not parametrised)" } else { "" })
+    }
+}
+
+pub struct BlueprintBook<'a> {
+    pub generation: u32,
+    pub label: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub icons: Vec<Option<Signal<'a>>>,
+    pub active_index: usize,
+    // include inactive slots
+    pub print_count: usize,
+    // (slot index, print)[], inactive slots are not included, same as library
+    pub prints: Vec<(usize, Print<'a>)>,
+}
+
+impl<'a> BlueprintBook<'a> {
+    // builders like Blueprint's, only used by tests for now
+    #[allow(dead_code)]
+    pub fn new(label: impl Into<Cow<'a, str>>) -> Self {
+        Self{ generation: 0, label: label.into(), description: Cow::Borrowed(""), icons: Vec::new(),
+            active_index: 0, print_count: 0, prints: Vec::new() }
+    }
+    #[allow(dead_code)]
+    pub fn set_label(&mut self, label: impl Into<Cow<'a, str>>) -> &mut Self {
+        self.label = label.into();
+        self
+    }
+    #[allow(dead_code)]
+    pub fn set_description(&mut self, description: impl Into<Cow<'a, str>>) -> &mut Self {
+        self.description = description.into();
+        self
+    }
+    // add to a new slot after all existing slots, return the slot index
+    #[allow(dead_code)]
+    pub fn add_print(&mut self, print: Print<'a>) -> usize {
+        let index = self.print_count;
+        self.prints.push((index, print));
+        self.print_count += 1;
+        index
+    }
+
+    pub fn into_owned(self) -> BlueprintBook<'static> {
+        BlueprintBook{
+            generation: self.generation,
+            label: owned(self.label),
+            description: owned(self.description),
+            icons: self.icons.into_iter().map(|icon| icon.map(Signal::into_owned)).collect(),
+            active_index: self.active_index,
+            print_count: self.print_count,
+            prints: self.prints.into_iter().map(|(index, print)| (index, print.into_owned())).collect(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for BlueprintBook<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "blueprint book {}", self.label)?;
+        if !self.description.is_empty() {
+            writeln!(f, "  description: {}", self.description)?;
+        }
+        for icon in self.icons.iter().flatten() {
+            writeln!(f, "  icon {:?}", icon)?;
+        }
+        writeln!(f, "  active index {}", self.active_index)?;
+        // indent content, nested books get deeper indentation by this
+        for (index, print) in &self.prints {
+            let content = format!("#{index} {:?}", print);
+            for line in content.lines() {
+                writeln!(f, "  {line}")?;
+            }
+        }
         Ok(())
     }
 }
-pub struct UpgradePlan {}
+pub struct UpgradePlan<'a> {
+    pub generation: u32,
+    pub label: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub icons: Vec<Option<Signal<'a>>>,
+    // include empty slots, like print count of book
+    pub mapping_count: usize,
+    // (slot index, mapping)[], empty slot from library file is included for its kind, see UpgradeMapping::is_empty
+    pub mappings: Vec<(usize, UpgradeMapping<'a>)>,
+}
+
+impl<'a> UpgradePlan<'a> {
+    pub fn into_owned(self) -> UpgradePlan<'static> {
+        UpgradePlan{
+            generation: self.generation,
+            label: owned(self.label),
+            description: owned(self.description),
+            icons: self.icons.into_iter().map(|icon| icon.map(Signal::into_owned)).collect(),
+            mapping_count: self.mapping_count,
+            mappings: self.mappings.into_iter().map(|(index, mapping)| (index, UpgradeMapping{
+                kind: mapping.kind,
+                from: mapping.from.map(|(name, quality)| (owned(name), quality.map(QualityCondition::into_owned))),
+                to: mapping.to.map(|(name, quality)| (owned(name), quality.map(owned))),
+            })).collect(),
+        }
+    }
+}
 
-impl fmt::Debug for UpgradePlan {
-    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+impl<'a> fmt::Debug for UpgradePlan<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "upgrade plan {}", self.label)?;
+        if !self.description.is_empty() {
+            writeln!(f, "  description: {}", self.description)?;
+        }
+        for icon in self.icons.iter().flatten() {
+            writeln!(f, "  icon {:?}", icon)?;
+        }
+        for (index, mapping) in self.mappings.iter().filter(|(_, mapping)| !mapping.is_empty()) {
+            writeln!(f, "  mapping #{index} {:?}", mapping)?;
+        }
         Ok(())
     }
 }
-pub struct DeconstructionPlan {}
 
-impl fmt::Debug for DeconstructionPlan {
-    fn fmt(&self, _f: &mut fmt::Formatter) -> fmt::Result {
+// item mapping is module mapping in game
+#[derive(Debug)]
+pub enum UpgradeKind {
+    Entity,
+    Item,
+}
+
+pub struct UpgradeMapping<'a> {
+    pub kind: UpgradeKind,
+    // (name, quality condition)
+    pub from: Option<(Cow<'a, str>, Option<QualityCondition<'a>>)>,
+    // (name, quality name), None quality for same as from
+    pub to: Option<(Cow<'a, str>, Option<Cow<'a, str>>)>,
+}
+
+impl<'a> UpgradeMapping<'a> {
+    // neither side is set, but kind is still kept in library file
+    pub fn is_empty(&self) -> bool {
+        self.from.is_none() && self.to.is_none()
+    }
+}
+
+impl<'a> fmt::Debug for UpgradeMapping<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} ", self.kind)?;
+        match &self.from {
+            Some((name, Some(quality))) => write!(f, "{name} quality {:?}", quality)?,
+            Some((name, None)) => write!(f, "{name}")?,
+            None => write!(f, "(empty)")?,
+        }
+        write!(f, " => ")?;
+        match &self.to {
+            Some((name, Some(quality))) => write!(f, "{name} quality {quality}"),
+            Some((name, None)) => write!(f, "{name}"),
+            None => write!(f, "(empty)"),
+        }
+    }
+}
+pub struct DeconstructionPlan<'a> {
+    pub generation: u32,
+    pub label: Cow<'a, str>,
+    pub description: Cow<'a, str>,
+    pub icons: Vec<Option<Signal<'a>>>,
+    pub entity_filter_mode: FilterMode,
+    // include empty slots, like print count of book
+    pub entity_filter_count: usize,
+    // (slot index, filter)[], empty slots are not included
+    pub entity_filters: Vec<(usize, EntityFilter<'a>)>,
+    pub trees_and_rocks_only: bool,
+    pub tile_filter_mode: FilterMode,
+    pub tile_selection_mode: TileSelectionMode,
+    // include empty slots
+    pub tile_filter_count: usize,
+    // (slot index, tile name)[], empty slots are not included
+    pub tile_filters: Vec<(usize, Cow<'a, str>)>,
+}
+
+impl<'a> DeconstructionPlan<'a> {
+    pub fn into_owned(self) -> DeconstructionPlan<'static> {
+        DeconstructionPlan{
+            generation: self.generation,
+            label: owned(self.label),
+            description: owned(self.description),
+            icons: self.icons.into_iter().map(|icon| icon.map(Signal::into_owned)).collect(),
+            entity_filter_mode: self.entity_filter_mode,
+            entity_filter_count: self.entity_filter_count,
+            entity_filters: self.entity_filters.into_iter().map(|(index, filter)| (index, filter.into_owned())).collect(),
+            trees_and_rocks_only: self.trees_and_rocks_only,
+            tile_filter_mode: self.tile_filter_mode,
+            tile_selection_mode: self.tile_selection_mode,
+            tile_filter_count: self.tile_filter_count,
+            tile_filters: self.tile_filters.into_iter().map(|(index, name)| (index, owned(name))).collect(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for DeconstructionPlan<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "deconstruction plan {}", self.label)?;
+        if !self.description.is_empty() {
+            writeln!(f, "  description: {}", self.description)?;
+        }
+        for icon in self.icons.iter().flatten() {
+            writeln!(f, "  icon {:?}", icon)?;
+        }
+        writeln!(f, "  entity filter mode {:?}", self.entity_filter_mode)?;
+        for (index, filter) in &self.entity_filters {
+            writeln!(f, "  entity filter #{index} {:?}", filter)?;
+        }
+        if self.trees_and_rocks_only {
+            writeln!(f, "  trees and rocks only")?;
+        }
+        writeln!(f, "  tile filter mode {:?}", self.tile_filter_mode)?;
+        writeln!(f, "  tile selection mode {:?}", self.tile_selection_mode)?;
+        for (index, name) in &self.tile_filters {
+            writeln!(f, "  tile filter #{index} {name}")?;
+        }
         Ok(())
     }
 }
 
+#[derive(Debug)]
+pub enum FilterMode {
+    Whitelist,
+    Blacklist,
+}
+
+#[derive(Debug)]
+pub enum TileSelectionMode {
+    Normal,
+    Always,
+    Never,
+    Only,
+}
+
+// entity filter in deconstruction plan, also item filter in inserter
+pub struct EntityFilter<'a> {
+    pub name: Cow<'a, str>,
+    // None for any quality
+    pub quality: Option<QualityCondition<'a>>,
+}
+
+impl<'a> EntityFilter<'a> {
+    pub fn into_owned(self) -> EntityFilter<'static> {
+        EntityFilter{ name: owned(self.name), quality: self.quality.map(QualityCondition::into_owned) }
+    }
+}
+
+impl<'a> fmt::Debug for EntityFilter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(quality) = &self.quality {
+            write!(f, " quality {:?}", quality)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct QualityCondition<'a> {
+    pub comparator: Comparator,
+    pub quality: Cow<'a, str>,
+}
+
+impl<'a> QualityCondition<'a> {
+    pub fn into_owned(self) -> QualityCondition<'static> {
+        QualityCondition{ comparator: self.comparator, quality: owned(self.quality) }
+    }
+}
+
+impl<'a> fmt::Debug for QualityCondition<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {}", self.comparator, self.quality)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Comparator {
+    GreaterThan,
+    LessThan,
+    Equal,
+    GreaterThanOrEqual,
+    LessThanOrEqual,
+    NotEqual,
+}
+
+impl Comparator {
+    // the symbol used in game and blueprint json format
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::GreaterThan => ">",
+            Self::LessThan => "<",
+            Self::Equal => "=",
+            Self::GreaterThanOrEqual => "≥",
+            Self::LessThanOrEqual => "≤",
+            Self::NotEqual => "≠",
+        }
+    }
+}
+
+impl fmt::Debug for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
 pub enum Print<'a> {
     Blueprint(Blueprint<'a>),
-    BlueprintBook(BlueprintBook),
-    UpgradePlan(UpgradePlan),
-    DeconstructionPlan(DeconstructionPlan),
+    BlueprintBook(BlueprintBook<'a>),
+    UpgradePlan(UpgradePlan<'a>),
+    DeconstructionPlan(DeconstructionPlan<'a>),
+}
+
+impl<'a> Print<'a> {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Blueprint(item) => &item.label,
+            Self::BlueprintBook(item) => &item.label,
+            Self::UpgradePlan(item) => &item.label,
+            Self::DeconstructionPlan(item) => &item.label,
+        }
+    }
+    // the item name in blueprint string
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Blueprint(_) => "blueprint",
+            Self::BlueprintBook(_) => "blueprint-book",
+            Self::UpgradePlan(_) => "upgrade-planner",
+            Self::DeconstructionPlan(_) => "deconstruction-planner",
+        }
+    }
+    pub fn into_owned(self) -> Print<'static> {
+        match self {
+            Self::Blueprint(item) => Print::Blueprint(item.into_owned()),
+            Self::BlueprintBook(item) => Print::BlueprintBook(item.into_owned()),
+            Self::UpgradePlan(item) => Print::UpgradePlan(item.into_owned()),
+            Self::DeconstructionPlan(item) => Print::DeconstructionPlan(item.into_owned()),
+        }
+    }
 }
+
 impl<'a> fmt::Debug for Print<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -97,89 +730,312 @@ pub struct SnapToGrid {
 pub struct BlueprintEntity<'a> {
     pub kind: EntityKind<'a>,
     pub position: (f64, f64),
-    pub entity_id: usize, // NOTE this is not blueprint json format's entity number
-    pub items: Vec<(&'a str, usize)>, // item name and count
+    pub entity_id: usize, // NOTE this is not blueprint json format's entity number, see Blueprint::entity_number
+    // NEW in 2.0, None for normal quality
+    pub quality: Option<Cow<'a, str>>,
+    pub items: Vec<(Cow<'a, str>, Option<Cow<'a, str>>, usize)>, // item name, quality and count
+    // (key, value)[], set by mods, empty for no tags
+    pub tags: Vec<(Cow<'a, str>, TagValue<'a>)>,
+}
+
+impl<'a> BlueprintEntity<'a> {
+    // builders like Blueprint's, only used by tests for now
+    #[allow(dead_code)]
+    pub fn add_item(&mut self, name: impl Into<Cow<'a, str>>, count: usize) -> &mut Self {
+        self.items.push((name.into(), None, count));
+        self
+    }
+    #[allow(dead_code)]
+    pub fn add_quality_item(&mut self, name: impl Into<Cow<'a, str>>, quality: impl Into<Cow<'a, str>>, count: usize) -> &mut Self {
+        self.items.push((name.into(), Some(quality.into()), count));
+        self
+    }
+    #[allow(dead_code)]
+    pub fn set_quality(&mut self, quality: impl Into<Cow<'a, str>>) -> &mut Self {
+        self.quality = Some(quality.into());
+        self
+    }
+    #[allow(dead_code)]
+    pub fn add_tag(&mut self, key: impl Into<Cow<'a, str>>, value: TagValue<'a>) -> &mut Self {
+        self.tags.push((key.into(), value));
+        self
+    }
+
+    // module inventory of crafting machines is included in items, not used by main for now
+    #[allow(dead_code)]
+    pub fn modules(&self) -> impl Iterator<Item = (&str, u8, usize)> {
+        self.items.iter().filter_map(|(name, _, count)| module_tier(name).map(|(_, tier)| (name.as_ref(), tier, *count)))
+    }
+
+    pub fn into_owned(self) -> BlueprintEntity<'static> {
+        BlueprintEntity{
+            kind: self.kind.into_owned(),
+            position: self.position,
+            entity_id: self.entity_id,
+            quality: self.quality.map(owned),
+            items: self.items.into_iter().map(|(name, quality, count)| (owned(name), quality.map(owned), count)).collect(),
+            tags: self.tags.into_iter().map(|(key, value)| (owned(key), value.into_owned())).collect(),
+        }
+    }
 }
 
 impl<'a> fmt::Debug for BlueprintEntity<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "  entity {} {:?} #{}", self.kind.name(), self.position, self.entity_id)?;
-        for (item_name, item_count) in &self.items {
-            writeln!(f, "    item {} x {}", item_name, item_count)?;
+        if let Some(quality) = &self.quality {
+            writeln!(f, "    quality {}", quality)?;
+        }
+        write!(f, "{:?}", self.kind)?;
+        for (item_name, item_quality, item_count) in &self.items {
+            match item_quality {
+                Some(quality) => writeln!(f, "    item {} ({}) x {}", item_name, quality, item_count)?,
+                None => writeln!(f, "    item {} x {}", item_name, item_count)?,
+            }
+        }
+        for (key, value) in &self.tags {
+            writeln!(f, "    tag {} = {:?}", key, value)?;
         }
         Ok(())
     }
 }
 
+// lua value in entity tags, tags and nested tables are dictionary
+#[derive(Clone, PartialEq)]
+pub enum TagValue<'a> {
+    Bool(bool),
+    Number(f64),
+    String(Cow<'a, str>),
+    List(Vec<TagValue<'a>>),
+    // (key, value)[]
+    Table(Vec<(Cow<'a, str>, TagValue<'a>)>),
+}
+
+impl<'a> TagValue<'a> {
+    pub fn into_owned(self) -> TagValue<'static> {
+        match self {
+            Self::Bool(value) => TagValue::Bool(value),
+            Self::Number(value) => TagValue::Number(value),
+            Self::String(value) => TagValue::String(owned(value)),
+            Self::List(values) => TagValue::List(values.into_iter().map(TagValue::into_owned).collect()),
+            Self::Table(entries) => TagValue::Table(entries.into_iter().map(|(key, value)| (owned(key), value.into_owned())).collect()),
+        }
+    }
+}
+
+// like lua table constructor, { 1, "a", { key = true } }
+impl<'a> fmt::Debug for TagValue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Number(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "{:?}", value),
+            Self::List(values) => {
+                write!(f, "{{")?;
+                for (index, value) in values.iter().enumerate() {
+                    write!(f, "{} {:?}", if index == 0 { "" } else { "," }, value)?;
+                }
+                write!(f, " }}")
+            },
+            Self::Table(entries) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    write!(f, "{} {} = {:?}", if index == 0 { "" } else { "," }, key, value)?;
+                }
+                write!(f, " }}")
+            },
+        }
+    }
+}
+
 pub struct CircuitConnections {
     // (entity id, circuit id)[], NOTE entity id is not blueprint json format's entity number
     pub red: Vec<(usize, usize)>,
     pub green: Vec<(usize, usize)>,
 }
 
-#[derive(Debug)]
+// connection point of wire on entity, by wire connector id in 2.0 format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireConnector {
+    // 1, input side of combinators, or the only side of other entities
+    Red,
+    // 2
+    Green,
+    // 3, output side of arithmetic and decider combinators
+    OutputRed,
+    // 4
+    OutputGreen,
+    // 5, electric pole, or left side of power switch
+    Copper,
+    // 6, right side of power switch
+    RightCopper,
+}
+
+impl WireConnector {
+    pub fn from_id(id: u8) -> Option<Self> {
+        Some(match id {
+            1 => Self::Red,
+            2 => Self::Green,
+            3 => Self::OutputRed,
+            4 => Self::OutputGreen,
+            5 => Self::Copper,
+            6 => Self::RightCopper,
+            _ => return None,
+        })
+    }
+    pub fn id(self) -> u8 {
+        self as u8 + 1
+    }
+    // 1.1 circuit id is 1 for input side and 2 for output side
+    pub fn from_circuit(red: bool, circuit_id: usize) -> Option<Self> {
+        Some(match (red, circuit_id) {
+            (true, 1) => Self::Red,
+            (false, 1) => Self::Green,
+            (true, 2) => Self::OutputRed,
+            (false, 2) => Self::OutputGreen,
+            _ => return None,
+        })
+    }
+}
+
+// wire between two entity connectors, entity is index in blueprint entity list
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Wire {
+    pub from: (usize, WireConnector),
+    pub to: (usize, WireConnector),
+}
+
+impl Wire {
+    // wire have no direction, ends are ordered so that same wire is equal
+    pub fn new(from: (usize, WireConnector), to: (usize, WireConnector)) -> Self {
+        if (to.0, to.1.id()) < (from.0, from.1.id()) { Self{ from: to, to: from } } else { Self{ from, to } }
+    }
+}
+
+// like "#0 Red - #3 OutputRed"
+impl fmt::Debug for Wire {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} {:?} - #{} {:?}", self.from.0, self.from.1, self.to.0, self.to.1)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub enum SignalKind {
     Item,
     Fluid,
     Virtual,
 }
 
+#[derive(Clone)]
 pub struct Signal<'a> {
     pub kind: SignalKind,
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
 }
 
-pub enum EntityKind<'a> {
-    Roboport(Roboport<'a>),
-    ExpressUndergroundBelt(UndergroundBelt),
+impl<'a> Signal<'a> {
+    // only used by tests for now
+    #[allow(dead_code)]
+    pub fn new(kind: SignalKind, name: impl Into<Cow<'a, str>>) -> Self {
+        Self{ kind, name: name.into() }
+    }
+    pub fn into_owned(self) -> Signal<'static> {
+        Signal{ kind: self.kind, name: owned(self.name) }
+    }
 }
 
-impl<'a> EntityKind<'a> {
-    pub fn name(&self) -> &'static str {
-        match self {
-            Self::Roboport(_) => "roboport",
-            Self::ExpressUndergroundBelt(_) => "express-underground-belt",
-        }
+impl<'a> fmt::Debug for Signal<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} {}", self.kind, self.name)
     }
 }
 
-pub struct Roboport<'a> {
-    pub circuit_connections: Option<CircuitConnections>,
-    // control behaviors
-    pub read_logistics: bool,
-    pub read_robot_stats: bool,
-    pub available_logistic_output_signal: Option<Signal<'a>>,
-    pub total_logistic_output_signal: Option<Signal<'a>>,
-    pub available_construction_output_signal: Option<Signal<'a>>,
-    pub total_construction_output_signal: Option<Signal<'a>>,
-    // NEW in 2.0
-    pub roboport_count_output_signal: Option<Signal<'a>>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl<'a> fmt::Debug for Roboport<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "    read_logistics {}", self.read_logistics)?;
-        writeln!(f, "    read_robots_stats {}", self.read_robot_stats)?;
-        if let Some(signal) = &self.available_logistic_output_signal {
-            writeln!(f, "    available_logistic_output_signal {:?} {}", signal.kind, signal.name)?;
-        }
-        if let Some(signal) = &self.total_logistic_output_signal {
-            writeln!(f, "    total_logistic_output_signal {:?} {}", signal.kind, signal.name)?;
-        }
-        if let Some(signal) = &self.available_construction_output_signal {
-            writeln!(f, "    available_construction_output_signal {:?} {}", signal.kind, signal.name)?;
-        }
-        if let Some(signal) = &self.total_construction_output_signal {
-            writeln!(f, "    total_construction_output_signal {:?} {}", signal.kind, signal.name)?;
-        }
-        if let Some(signal) = &self.roboport_count_output_signal {
-            writeln!(f, "    roboport_count_output_signal {:?} {}", signal.kind, signal.name)?;
+    // 1.1 circuit connections are in both ends, and combinator have output side
+    #[test]
+    fn collect_wires_1_1() {
+        let mut blueprint = Blueprint::new("wires", (1, 1, 110, 0));
+        blueprint.add_entity(EntityKind::Roboport(Roboport{
+            circuit_connections: Some(CircuitConnections{ red: vec![(2, 1)], green: Vec::new() }),
+            read_logistics: false,
+            read_robot_stats: false,
+            available_logistic_output_signal: None,
+            total_logistic_output_signal: None,
+            available_construction_output_signal: None,
+            total_construction_output_signal: None,
+            roboport_count_output_signal: None,
+        }), (2.0, 2.0)).entity_id = 1;
+        blueprint.add_entity(EntityKind::TransportBelt(TransportBelt{ tier: BeltTier::Normal, direction: 2,
+            circuit_connections: Some(CircuitConnections{ red: vec![(1, 1)], green: Vec::new() }),
+            circuit_condition: None, read_contents: Some(BeltReadMode::Hold) }), (6.5, 2.5)).entity_id = 2;
+        blueprint.add_entity(EntityKind::Inserter(Inserter{
+            tier: InserterTier::Filter,
+            direction: 6,
+            circuit_connections: Some(CircuitConnections{ red: Vec::new(), green: vec![(2, 1)] }),
+            use_filters: false,
+            filter_mode: FilterMode::Whitelist,
+            filters: Vec::new(),
+            stack_size_override: None,
+            circuit_condition: None,
+            logistic_condition: None,
+            read_hand_contents: None,
+            set_filters: false,
+            set_stack_size: None,
+        }), (8.5, 2.5)).entity_id = 3;
+        blueprint.add_entity(EntityKind::DeciderCombinator(DeciderCombinator{ direction: 2,
+            input_connections: Some(CircuitConnections{ red: vec![(1, 1)], green: Vec::new() }),
+            output_connections: Some(CircuitConnections{ red: Vec::new(), green: vec![(3, 1)] }),
+            conditions: Vec::new(), outputs: Vec::new() }), (12.5, 2.0));
+        // pole - switch (off) - pole, 1.1 pole neighbours are in both poles, switch connections only in switch
+        blueprint.add_entity(EntityKind::ElectricPole(ElectricPole{ pole: PoleType::Small, circuit_connections: None,
+            copper_connections: Some(vec![]) }), (30.5, 0.5)).entity_id = 20;
+        blueprint.add_entity(EntityKind::ElectricPole(ElectricPole{ pole: PoleType::Small, circuit_connections: None,
+            copper_connections: Some(vec![23]) }), (34.5, 0.5)).entity_id = 21;
+        blueprint.add_entity(EntityKind::PowerSwitch(PowerSwitch{ circuit_connections: None,
+            copper_connections: Some([vec![20], vec![21]]), switch_state: false, circuit_condition: None, logistic_condition: None }),
+            (32.0, 1.0)).entity_id = 22;
+        blueprint.add_entity(EntityKind::ElectricPole(ElectricPole{ pole: PoleType::Substation, circuit_connections: None,
+            copper_connections: Some(vec![21]) }), (38.0, 0.0)).entity_id = 23;
+
+        assert_eq!(format!("{:?}", blueprint.collect_wires()), "[#0 Red - #1 Red, #1 Green - #2 Green, #0 Red - #3 Red, #2 Green - #3 OutputGreen, \
+            #5 Copper - #7 Copper, #4 Copper - #6 Copper, #5 Copper - #6 RightCopper]");
+        // switch is off so two networks
+        assert_eq!(blueprint.electric_networks(), [vec![4], vec![5, 7]]);
+    }
+
+    // substation - pole - switch (on) - pole, and a lone pole
+    #[test]
+    fn electric_networks() {
+        let mut blueprint = Blueprint::new("poles", (2, 0, 28, 1));
+        for (pole, position) in [(PoleType::Substation, (360.0, 0.0)), (PoleType::Small, (365.5, 0.5)),
+            (PoleType::Small, (369.5, 0.5)), (PoleType::Small, (390.5, 0.5))] {
+            blueprint.add_entity(EntityKind::ElectricPole(ElectricPole{ pole, circuit_connections: None, copper_connections: None }), position);
         }
-        Ok(())
+        blueprint.add_entity(EntityKind::PowerSwitch(PowerSwitch{ circuit_connections: None, copper_connections: None, switch_state: true,
+            circuit_condition: None, logistic_condition: None }), (367.0, 1.0));
+        blueprint.wires.push(Wire::new((0, WireConnector::Copper), (1, WireConnector::Copper)));
+        blueprint.wires.push(Wire::new((1, WireConnector::Copper), (4, WireConnector::Copper)));
+        blueprint.wires.push(Wire::new((4, WireConnector::RightCopper), (2, WireConnector::Copper)));
+        // circuit wires do not connect electric networks
+        blueprint.wires.push(Wire::new((3, WireConnector::Red), (2, WireConnector::Red)));
+        assert_eq!(blueprint.electric_networks(), [vec![0, 1, 2], vec![3]]);
+        // wire to entity not in list is ignored
+        blueprint.wires.push(Wire::new((3, WireConnector::Copper), (9, WireConnector::Copper)));
+        assert_eq!(blueprint.electric_networks(), [vec![0, 1, 2], vec![3]]);
+
+        blueprint.entities[4].kind = EntityKind::PowerSwitch(PowerSwitch{ circuit_connections: None, copper_connections: None,
+            switch_state: false, circuit_condition: None, logistic_condition: None });
+        assert_eq!(blueprint.electric_networks(), [vec![0, 1], vec![2], vec![3]]);
     }
-}
 
-pub struct UndergroundBelt {
-    pub direction: usize,
-    pub output: bool, // false for input, true for output
+    #[test]
+    fn modules() {
+        let mut blueprint = Blueprint::new("modules", (2, 0, 28, 1));
+        blueprint.add_entity(EntityKind::CraftingMachine(CraftingMachine{ machine: CraftingMachineType::AssemblingMachine3, direction: 0,
+            mirrored: false, recipe: Some("iron-gear-wheel".into()), recipe_quality: None }), (10.5, 2.5))
+            .add_item("speed-module", 2).add_item("speed-module-2", 2).add_item("iron-plate", 10);
+        let modules = blueprint.entities[0].modules().collect::<Vec<_>>();
+        assert_eq!(modules, [("speed-module", 1, 2), ("speed-module-2", 2, 2)]);
+    }
 }
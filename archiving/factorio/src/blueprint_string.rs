@@ -0,0 +1,1847 @@
+// blueprint exchange string, the text used by in game import/export
+// the format is 1 byte version + base64(zlib(json)), see also blueprint.py and schema.ts
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use anyhow::{anyhow, bail};
+use base64::Engine;
+use chrono::DateTime;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use serde_json::{json, Map, Value};
+
+use crate::blueprint_library::*;
+use crate::name::Names;
+
+// version byte is always '0' for now
+const STRING_VERSION: char = '0';
+
+// library file version is used as version of book and planners, they don't have their own version
+pub fn encode(print: &Print, file_version: Version) -> anyhow::Result<String> {
+    encode_json(&print_to_json(print, file_version))
+}
+
+// the json object wrapped by print type name, like from print_to_json or library_to_json
+pub fn encode_json(json: &Value) -> anyhow::Result<String> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(serde_json::to_string(json)?.as_bytes())?;
+    let compressed = encoder.finish()?;
+
+    let mut result = String::with_capacity(compressed.len() * 4 / 3 + 4);
+    result.push(STRING_VERSION);
+    base64::engine::general_purpose::STANDARD.encode_string(compressed, &mut result);
+    Ok(result)
+}
+
+// decode to json value, use print_from_json to get the print,
+// the print borrows strings from the json value, so this is not combined into one function
+pub fn decode(text: &str) -> anyhow::Result<Value> {
+    let text = text.trim();
+    let Some(content) = text.strip_prefix(STRING_VERSION) else {
+        bail!("unknown exchange string version {:?}", text.chars().next());
+    };
+    let compressed = base64::engine::general_purpose::STANDARD.decode(content)?;
+    let mut decompressed = Vec::new();
+    ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut decompressed)?;
+    Ok(serde_json::from_slice(&decompressed)?)
+}
+
+// the version is a u64 with each part in 16 bits
+pub fn version_to_json(version: Version) -> u64 {
+    (version.0 as u64) << 48 | (version.1 as u64) << 32 | (version.2 as u64) << 16 | version.3 as u64
+}
+pub fn version_from_json(version: u64) -> Version {
+    ((version >> 48) as u16, (version >> 32) as u16, (version >> 16) as u16, version as u16)
+}
+
+// the json object wrapped by print type name, this is also blueprint book's blueprints' item
+pub fn print_to_json(print: &Print, file_version: Version) -> Value {
+    match print {
+        Print::Blueprint(blueprint) => json!({ "blueprint": blueprint_to_json(blueprint) }),
+        Print::BlueprintBook(book) => json!({ "blueprint_book": book_to_json(book, file_version) }),
+        Print::DeconstructionPlan(plan) => json!({ "deconstruction_planner": deconstruction_plan_to_json(plan, file_version) }),
+        Print::UpgradePlan(plan) => json!({ "upgrade_planner": upgrade_plan_to_json(plan, file_version) }),
+    }
+}
+
+fn blueprint_to_json(blueprint: &Blueprint) -> Value {
+    let mut result = Map::new();
+    result.insert("item".into(), "blueprint".into());
+    if !blueprint.label.is_empty() {
+        result.insert("label".into(), blueprint.label.as_ref().into());
+    }
+    if !blueprint.description.is_empty() {
+        result.insert("description".into(), blueprint.description.as_ref().into());
+    }
+    insert_icons(&mut result, &blueprint.icons);
+    if let Some(snap) = &blueprint.snap_to_grid {
+        result.insert("snap-to-grid".into(), json!({ "x": snap.size.0, "y": snap.size.1 }));
+        if snap.absolute != (0, 0) {
+            result.insert("absolute-snapping".into(), true.into());
+            result.insert("position-relative-to-grid".into(), json!({ "x": snap.absolute.0, "y": snap.absolute.1 }));
+        }
+    }
+    if !blueprint.entities.is_empty() {
+        let entities = blueprint.entities.iter().enumerate()
+            .map(|(index, entity)| entity_to_json(entity, index + 1)).collect::<Vec<_>>();
+        result.insert("entities".into(), entities.into());
+    }
+    if !blueprint.tiles.is_empty() {
+        let tiles = blueprint.tiles.iter()
+            .map(|(name, (x, y))| json!({ "name": name, "position": { "x": x, "y": y } })).collect::<Vec<_>>();
+        result.insert("tiles".into(), tiles.into());
+    }
+    // schedules are in blueprint with locomotive entity numbers
+    let schedules = blueprint.entities.iter().enumerate().filter_map(|(index, entity)| match &entity.kind {
+        EntityKind::Locomotive(Locomotive{ schedule: Some(schedule), .. }) =>
+            Some(json!({ "locomotives": [index + 1], "schedule": schedule_to_json(schedule) })),
+        _ => None,
+    }).collect::<Vec<_>>();
+    if !schedules.is_empty() {
+        result.insert("schedules".into(), schedules.into());
+    }
+    // [entity number, connector id, entity number, connector id][]
+    let wires = blueprint.collect_wires().iter()
+        .map(|wire| json!([wire.from.0 + 1, wire.from.1.id(), wire.to.0 + 1, wire.to.1.id()])).collect::<Vec<_>>();
+    if !wires.is_empty() {
+        result.insert("wires".into(), wires.into());
+    }
+    if !blueprint.parameters.is_empty() {
+        let parameters = blueprint.parameters.iter().map(parameter_to_json).collect::<Vec<_>>();
+        result.insert("parameters".into(), parameters.into());
+    }
+    result.insert("version".into(), version_to_json(blueprint.version).into());
+    result.into()
+}
+
+fn parameter_to_json(parameter: &BlueprintParameter) -> Value {
+    let mut result = Map::new();
+    match &parameter.kind {
+        ParameterKind::Id{ id, quality_condition, ingredient_of } => {
+            result.insert("type".into(), "id".into());
+            result.insert("name".into(), parameter.name.as_ref().into());
+            result.insert("id".into(), id.as_ref().into());
+            if let Some(quality) = quality_condition {
+                result.insert("quality-condition".into(), json!({ "quality": quality.quality, "comparator": quality.comparator.symbol() }));
+            }
+            if let Some(ingredient_of) = ingredient_of {
+                result.insert("ingredient-of".into(), ingredient_of.as_ref().into());
+            }
+        },
+        ParameterKind::Number{ number, variable, formula, dependent } => {
+            result.insert("type".into(), "number".into());
+            result.insert("name".into(), parameter.name.as_ref().into());
+            result.insert("number".into(), number.as_ref().into());
+            if let Some(variable) = variable {
+                result.insert("variable".into(), variable.as_ref().into());
+            }
+            if let Some(formula) = formula {
+                result.insert("formula".into(), formula.as_ref().into());
+            }
+            if *dependent {
+                result.insert("dependent".into(), true.into());
+            }
+        },
+    }
+    if parameter.not_parametrised {
+        result.insert("not-parametrised".into(), true.into());
+    }
+    result.into()
+}
+
+fn schedule_to_json(schedule: &Schedule) -> Value {
+    let mut result = Map::new();
+    result.insert("records".into(), schedule_records_to_json(&schedule.records));
+    if !schedule.interrupts.is_empty() {
+        result.insert("interrupts".into(), schedule.interrupts.iter().map(|interrupt| {
+            let mut item = Map::new();
+            item.insert("name".into(), interrupt.name.as_ref().into());
+            item.insert("conditions".into(), wait_conditions_to_json(&interrupt.conditions));
+            item.insert("targets".into(), schedule_records_to_json(&interrupt.targets));
+            if interrupt.inside_interrupt {
+                item.insert("inside_interrupt".into(), true.into());
+            }
+            item.into()
+        }).collect::<Vec<Value>>().into());
+    }
+    result.into()
+}
+
+fn schedule_records_to_json(records: &[ScheduleRecord]) -> Value {
+    records.iter().map(|record| {
+        let mut item = Map::new();
+        item.insert("station".into(), record.station.as_ref().into());
+        if !record.wait_conditions.is_empty() {
+            item.insert("wait_conditions".into(), wait_conditions_to_json(&record.wait_conditions));
+        }
+        if record.temporary {
+            item.insert("temporary".into(), true.into());
+        }
+        item.into()
+    }).collect::<Vec<Value>>().into()
+}
+
+fn wait_conditions_to_json(conditions: &[WaitCondition]) -> Value {
+    conditions.iter().map(|condition| {
+        let mut item = Map::new();
+        item.insert("type".into(), condition.kind.name().into());
+        item.insert("compare_type".into(), (match condition.join {
+            ConditionJoin::Or => "or",
+            ConditionJoin::And => "and",
+        }).into());
+        match &condition.kind {
+            WaitConditionKind::Time{ ticks } | WaitConditionKind::Inactivity{ ticks } => { item.insert("ticks".into(), (*ticks).into()); },
+            WaitConditionKind::DamageTaken{ damage } => { item.insert("damage".into(), (*damage).into()); },
+            WaitConditionKind::ItemCount(circuit_condition) | WaitConditionKind::Circuit(circuit_condition)
+            | WaitConditionKind::FluidCount(circuit_condition) | WaitConditionKind::FuelItemCountAll(circuit_condition)
+            | WaitConditionKind::FuelItemCountAny(circuit_condition) => {
+                item.insert("condition".into(), circuit_condition_to_json(circuit_condition));
+            },
+            WaitConditionKind::SpecificDestinationFull(station) | WaitConditionKind::SpecificDestinationNotFull(station)
+            | WaitConditionKind::AtStation(station) | WaitConditionKind::NotAtStation(station) => {
+                item.insert("station".into(), station.as_ref().into());
+            },
+            _ => {},
+        }
+        item.into()
+    }).collect::<Vec<Value>>().into()
+}
+
+// entity number is 1 based index in entity list, see Blueprint::entity_number
+fn entity_to_json(entity: &BlueprintEntity, entity_number: usize) -> Value {
+    let mut result = Map::new();
+    result.insert("entity_number".into(), entity_number.into());
+    result.insert("name".into(), entity.kind.name().into());
+    result.insert("position".into(), json!({ "x": entity.position.0, "y": entity.position.1 }));
+    if let Some(quality) = &entity.quality {
+        result.insert("quality".into(), quality.as_ref().into());
+    }
+
+    match &entity.kind {
+        EntityKind::Roboport(roboport) => {
+            let mut behavior = Map::new();
+            behavior.insert("read_logistics".into(), roboport.read_logistics.into());
+            behavior.insert("read_robot_stats".into(), roboport.read_robot_stats.into());
+            for (key, signal) in [
+                ("available_logistic_output_signal", &roboport.available_logistic_output_signal),
+                ("total_logistic_output_signal", &roboport.total_logistic_output_signal),
+                ("available_construction_output_signal", &roboport.available_construction_output_signal),
+                ("total_construction_output_signal", &roboport.total_construction_output_signal),
+                ("roboport_count_output_signal", &roboport.roboport_count_output_signal),
+            ] {
+                if let Some(signal) = signal {
+                    behavior.insert(key.into(), signal_to_json(signal));
+                }
+            }
+            result.insert("control_behavior".into(), behavior.into());
+        },
+        EntityKind::TransportBelt(belt) => {
+            insert_direction(&mut result, belt.direction);
+            let mut behavior = Map::new();
+            if let Some(condition) = &belt.circuit_condition {
+                behavior.insert("circuit_enabled".into(), true.into());
+                behavior.insert("circuit_condition".into(), circuit_condition_to_json(condition));
+            }
+            if let Some(mode) = belt.read_contents {
+                behavior.insert("circuit_read_hand_contents".into(), true.into());
+                behavior.insert("circuit_contents_read_mode".into(), (match mode {
+                    BeltReadMode::Pulse => 0,
+                    BeltReadMode::Hold => 1,
+                    BeltReadMode::EntireBeltHold => 2,
+                }).into());
+            }
+            if !behavior.is_empty() {
+                result.insert("control_behavior".into(), behavior.into());
+            }
+        },
+        EntityKind::UndergroundBelt(belt) => {
+            insert_direction(&mut result, belt.direction);
+            result.insert("type".into(), (if belt.output { "output" } else { "input" }).into());
+        },
+        EntityKind::Splitter(splitter) => {
+            insert_direction(&mut result, splitter.direction);
+            for (key, priority) in [("input_priority", splitter.input_priority), ("output_priority", splitter.output_priority)] {
+                match priority {
+                    SplitterPriority::None => {},
+                    SplitterPriority::Left => { result.insert(key.into(), "left".into()); },
+                    SplitterPriority::Right => { result.insert(key.into(), "right".into()); },
+                }
+            }
+            if let Some(filter) = &splitter.filter {
+                result.insert("filter".into(), json!({ "name": filter }));
+            }
+        },
+        EntityKind::Loader(loader) => {
+            insert_direction(&mut result, loader.direction);
+            result.insert("type".into(), (if loader.output { "output" } else { "input" }).into());
+        },
+        EntityKind::Inserter(inserter) => {
+            insert_direction(&mut result, inserter.direction);
+            if inserter.use_filters {
+                result.insert("use_filters".into(), true.into());
+                if let FilterMode::Blacklist = inserter.filter_mode {
+                    result.insert("filter_mode".into(), "blacklist".into());
+                }
+                if !inserter.filters.is_empty() {
+                    result.insert("filters".into(), filters_to_json(&inserter.filters));
+                }
+            }
+            if let Some(stack_size) = inserter.stack_size_override {
+                result.insert("override_stack_size".into(), stack_size.into());
+            }
+            let mut behavior = Map::new();
+            if let Some(condition) = &inserter.circuit_condition {
+                behavior.insert("circuit_enabled".into(), true.into());
+                behavior.insert("circuit_condition".into(), circuit_condition_to_json(condition));
+            }
+            if let Some(condition) = &inserter.logistic_condition {
+                behavior.insert("connect_to_logistic_network".into(), true.into());
+                behavior.insert("logistic_condition".into(), circuit_condition_to_json(condition));
+            }
+            if let Some(mode) = inserter.read_hand_contents {
+                behavior.insert("circuit_read_hand_contents".into(), true.into());
+                behavior.insert("circuit_hand_read_mode".into(), (match mode {
+                    HandReadMode::Pulse => 0,
+                    HandReadMode::Hold => 1,
+                }).into());
+            }
+            if inserter.set_filters {
+                behavior.insert("circuit_set_filters".into(), true.into());
+            }
+            if let Some(signal) = &inserter.set_stack_size {
+                behavior.insert("circuit_set_stack_size".into(), true.into());
+                if let Some(signal) = signal {
+                    behavior.insert("stack_control_input_signal".into(), signal_to_json(signal));
+                }
+            }
+            if !behavior.is_empty() {
+                result.insert("control_behavior".into(), behavior.into());
+            }
+        },
+        EntityKind::CraftingMachine(machine) => {
+            insert_direction(&mut result, machine.direction);
+            if machine.mirrored {
+                result.insert("mirror".into(), true.into());
+            }
+            if let Some(recipe) = &machine.recipe {
+                result.insert("recipe".into(), recipe.as_ref().into());
+            }
+            if let Some(quality) = &machine.recipe_quality {
+                result.insert("recipe_quality".into(), quality.as_ref().into());
+            }
+        },
+        EntityKind::ArithmeticCombinator(combinator) => {
+            insert_direction(&mut result, combinator.direction);
+            let mut conditions = Map::new();
+            insert_operand(&mut conditions, "first_signal", "first_constant", &combinator.first);
+            conditions.insert("operation".into(), combinator.operation.symbol().into());
+            insert_operand(&mut conditions, "second_signal", "second_constant", &combinator.second);
+            if let Some(signal) = &combinator.output_signal {
+                conditions.insert("output_signal".into(), signal_to_json(signal));
+            }
+            result.insert("control_behavior".into(), json!({ "arithmetic_conditions": conditions }));
+        },
+        EntityKind::DeciderCombinator(combinator) => {
+            insert_direction(&mut result, combinator.direction);
+            let conditions = combinator.conditions.iter().enumerate().map(|(index, condition)| {
+                let mut item = Map::new();
+                if let Some(signal) = &condition.condition.first_signal {
+                    item.insert("first_signal".into(), signal_to_json(signal));
+                }
+                insert_wire_networks(&mut item, "first_signal_networks", &condition.first_networks);
+                item.insert("comparator".into(), condition.condition.comparator.symbol().into());
+                insert_operand(&mut item, "second_signal", "constant", &condition.condition.second);
+                insert_wire_networks(&mut item, "second_signal_networks", &condition.second_networks);
+                if index > 0 && condition.join == ConditionJoin::And {
+                    item.insert("compare_type".into(), "and".into());
+                }
+                item.into()
+            }).collect::<Vec<Value>>();
+            let outputs = combinator.outputs.iter().map(|output| {
+                let mut item = Map::new();
+                if let Some(signal) = &output.signal {
+                    item.insert("signal".into(), signal_to_json(signal));
+                }
+                if output.copy_count_from_input {
+                    insert_wire_networks(&mut item, "networks", &output.networks);
+                } else {
+                    item.insert("copy_count_from_input".into(), false.into());
+                    if output.constant != 1 {
+                        item.insert("constant".into(), output.constant.into());
+                    }
+                }
+                item.into()
+            }).collect::<Vec<Value>>();
+            result.insert("control_behavior".into(), json!({ "decider_conditions": { "conditions": conditions, "outputs": outputs } }));
+        },
+        EntityKind::ConstantCombinator(combinator) => {
+            insert_direction(&mut result, combinator.direction);
+            let mut behavior = Map::new();
+            if !combinator.sections.is_empty() {
+                behavior.insert("sections".into(), logistic_sections_to_json(&combinator.sections));
+            }
+            if !combinator.enabled {
+                behavior.insert("is_on".into(), false.into());
+            }
+            if !behavior.is_empty() {
+                result.insert("control_behavior".into(), behavior.into());
+            }
+        },
+        EntityKind::Rail(rail) => insert_direction(&mut result, rail.direction),
+        EntityKind::RailSignal(signal) => {
+            insert_direction(&mut result, signal.direction);
+            let mut behavior = Map::new();
+            if let Some(condition) = &signal.close_condition {
+                behavior.insert("circuit_close_signal".into(), true.into());
+                behavior.insert("circuit_condition".into(), circuit_condition_to_json(condition));
+            }
+            if let Some(signals) = &signal.read_signals {
+                behavior.insert("circuit_read_signal".into(), true.into());
+                for (key, output_signal) in ["red_output_signal", "orange_output_signal", "green_output_signal", "blue_output_signal"].iter().zip(signals) {
+                    if let Some(output_signal) = output_signal {
+                        behavior.insert((*key).into(), signal_to_json(output_signal));
+                    }
+                }
+            }
+            if !behavior.is_empty() {
+                result.insert("control_behavior".into(), behavior.into());
+            }
+        },
+        EntityKind::TrainStop(stop) => {
+            insert_direction(&mut result, stop.direction);
+            result.insert("station".into(), stop.station.as_ref().into());
+            if let Some(limit) = stop.train_limit {
+                result.insert("manual_trains_limit".into(), limit.into());
+            }
+            if stop.priority != 50 {
+                result.insert("priority".into(), stop.priority.into());
+            }
+            let mut behavior = Map::new();
+            if let Some(condition) = &stop.enable_condition {
+                behavior.insert("circuit_enable_disable".into(), true.into());
+                behavior.insert("circuit_condition".into(), circuit_condition_to_json(condition));
+            }
+            if stop.send_to_train {
+                behavior.insert("send_to_train".into(), true.into());
+            }
+            if stop.read_from_train {
+                behavior.insert("read_from_train".into(), true.into());
+            }
+            for (flag, key, signal) in [
+                ("read_stopped_train", "train_stopped_signal", &stop.read_stopped_train),
+                ("set_trains_limit", "trains_limit_signal", &stop.set_trains_limit),
+                ("read_trains_count", "trains_count_signal", &stop.read_trains_count),
+                ("set_priority", "priority_signal", &stop.set_priority),
+            ] {
+                if let Some(signal) = signal {
+                    behavior.insert(flag.into(), true.into());
+                    if let Some(signal) = signal {
+                        behavior.insert(key.into(), signal_to_json(signal));
+                    }
+                }
+            }
+            if !behavior.is_empty() {
+                result.insert("control_behavior".into(), behavior.into());
+            }
+        },
+        EntityKind::Locomotive(locomotive) => {
+            result.insert("orientation".into(), locomotive.orientation.into());
+        },
+        EntityKind::Container(container) => {
+            if let Some(bar) = container.bar {
+                result.insert("bar".into(), bar.into());
+            }
+            if let Some(filter) = &container.filter {
+                result.insert("filters".into(), vec![filter_to_json(0, filter)].into());
+            }
+            if let Some(request) = &container.request {
+                insert_logistic_request(&mut result, request);
+            }
+        },
+        EntityKind::CargoWagon(wagon) => {
+            result.insert("orientation".into(), wagon.orientation.into());
+            let mut inventory = Map::new();
+            if !wagon.filters.is_empty() {
+                inventory.insert("filters".into(), filters_to_json(&wagon.filters));
+            }
+            if let Some(bar) = wagon.bar {
+                inventory.insert("bar".into(), bar.into());
+            }
+            if !inventory.is_empty() {
+                result.insert("inventory".into(), inventory.into());
+            }
+        },
+        EntityKind::SpacePlatformHub(hub) => insert_logistic_request(&mut result, &hub.request),
+        // copper connections are in blueprint wires
+        EntityKind::ElectricPole(_) => {},
+        EntityKind::PowerSwitch(switch) => {
+            result.insert("switch_state".into(), switch.switch_state.into());
+            let mut behavior = Map::new();
+            if let Some(condition) = &switch.circuit_condition {
+                behavior.insert("circuit_enable_disable".into(), true.into());
+                behavior.insert("circuit_condition".into(), circuit_condition_to_json(condition));
+            }
+            if let Some(condition) = &switch.logistic_condition {
+                behavior.insert("connect_to_logistic_network".into(), true.into());
+                behavior.insert("logistic_condition".into(), circuit_condition_to_json(condition));
+            }
+            if !behavior.is_empty() {
+                result.insert("control_behavior".into(), behavior.into());
+            }
+        },
+        EntityKind::Accumulator(accumulator) => {
+            if let Some(signal) = &accumulator.output_signal {
+                result.insert("control_behavior".into(), json!({ "output_signal": signal_to_json(signal) }));
+            }
+        },
+        EntityKind::PowerProducer(producer) => insert_direction(&mut result, producer.direction),
+        EntityKind::SelectorCombinator(combinator) => {
+            insert_direction(&mut result, combinator.direction);
+            let mut behavior = Map::new();
+            behavior.insert("operation".into(), combinator.mode.name().into());
+            match &combinator.mode {
+                SelectorMode::SelectInput{ select_max, index } => {
+                    behavior.insert("select_max".into(), (*select_max).into());
+                    insert_operand(&mut behavior, "index_signal", "index_constant", index);
+                },
+                SelectorMode::CountInputs{ output_signal: Some(signal) } => {
+                    behavior.insert("count_signal".into(), signal_to_json(signal));
+                },
+                SelectorMode::RandomInput{ interval } => {
+                    behavior.insert("random_update_interval".into(), (*interval).into());
+                },
+                SelectorMode::QualityFilter{ quality: Some(quality) } => {
+                    behavior.insert("quality_filter".into(), json!({ "quality": quality.quality, "comparator": quality.comparator.symbol() }));
+                },
+                SelectorMode::QualityTransfer{ source, destination_signal } => {
+                    match source {
+                        QualitySource::Static(quality) => {
+                            behavior.insert("quality_source_static".into(), json!({ "name": quality }));
+                        },
+                        QualitySource::Signal(signal) => {
+                            behavior.insert("select_quality_from_signal".into(), true.into());
+                            if let Some(signal) = signal {
+                                behavior.insert("quality_source_signal".into(), signal_to_json(signal));
+                            }
+                        },
+                    }
+                    if let Some(signal) = destination_signal {
+                        behavior.insert("quality_destination_signal".into(), signal_to_json(signal));
+                    }
+                },
+                _ => {},
+            }
+            result.insert("control_behavior".into(), behavior.into());
+        },
+        EntityKind::Unknown(unknown) => for (key, value) in &unknown.fields {
+            result.insert(key.clone(), value.clone());
+        },
+    }
+
+    // ATTENTION inventory position is not decoded, stacks are filled in item order of each inventory,
+    // modules take one stack each, other item type is put in one stack, in_inventory is left out if inventory is not known
+    if !entity.items.is_empty() {
+        let mut next_stacks = HashMap::<u8, usize>::new();
+        let items = entity.items.iter().map(|(name, quality, count)| {
+            let mut item = json!({ "id": { "name": name }, "items": {} });
+            if let Some(quality) = quality {
+                item["id"]["quality"] = quality.as_ref().into();
+            }
+            if let Some(inventory) = item_inventory(&entity.kind, name) {
+                let stack = next_stacks.entry(inventory).or_default();
+                let positions = if module_tier(name).is_some() {
+                    (*stack..*stack + count).map(|stack| json!({ "inventory": inventory, "stack": stack })).collect::<Vec<_>>()
+                } else {
+                    vec![json!({ "inventory": inventory, "stack": *stack, "count": count })]
+                };
+                *stack += positions.len();
+                item["items"]["in_inventory"] = positions.into();
+            }
+            item
+        }).collect::<Vec<_>>();
+        result.insert("items".into(), items.into());
+    }
+    if !entity.tags.is_empty() {
+        result.insert("tags".into(), tag_table_to_json(&entity.tags));
+    }
+    result.into()
+}
+
+// inventory index in defines.inventory of entity that the item is put in, None for not known
+fn item_inventory(kind: &EntityKind, item_name: &str) -> Option<u8> {
+    match kind {
+        EntityKind::CraftingMachine(_) if module_tier(item_name).is_some() => Some(4), // crafter_modules
+        EntityKind::Roboport(_) if item_name == "construction-robot" || item_name == "logistic-robot" => Some(1), // roboport_robot
+        EntityKind::Roboport(_) if item_name == "repair-pack" => Some(2), // roboport_material
+        EntityKind::Container(_) | EntityKind::CargoWagon(_) => Some(1), // chest and cargo_wagon
+        EntityKind::Locomotive(_) => Some(1), // fuel
+        _ => None,
+    }
+}
+
+fn tag_table_to_json(entries: &[(Cow<str>, TagValue)]) -> Value {
+    entries.iter().map(|(key, value)| (key.to_string(), tag_to_json(value))).collect::<Map<_, _>>().into()
+}
+
+fn tag_to_json(value: &TagValue) -> Value {
+    match value {
+        TagValue::Bool(value) => (*value).into(),
+        TagValue::Number(value) => (*value).into(),
+        TagValue::String(value) => value.as_ref().into(),
+        TagValue::List(values) => values.iter().map(tag_to_json).collect::<Vec<_>>().into(),
+        TagValue::Table(entries) => tag_table_to_json(entries),
+    }
+}
+
+fn book_to_json(book: &BlueprintBook, file_version: Version) -> Value {
+    let mut result = Map::new();
+    result.insert("item".into(), "blueprint-book".into());
+    result.insert("label".into(), book.label.as_ref().into());
+    if !book.description.is_empty() {
+        result.insert("description".into(), book.description.as_ref().into());
+    }
+    insert_icons(&mut result, &book.icons);
+    result.insert("blueprints".into(), book_items_to_json(&book.prints, file_version));
+    result.insert("active_index".into(), book.active_index.into());
+    result.insert("version".into(), version_to_json(file_version).into());
+    result.into()
+}
+
+// whole library as one book without label, slot index is kept as book index
+pub fn library_to_json(library: &BlueprintLibrary) -> Value {
+    json!({ "blueprint_book": {
+        "item": "blueprint-book",
+        "label": "",
+        "blueprints": book_items_to_json(&library.prints, library.file_version),
+        "active_index": 0,
+        "version": version_to_json(library.file_version),
+    } })
+}
+
+fn book_items_to_json(prints: &[(usize, Print)], file_version: Version) -> Value {
+    prints.iter().map(|(index, print)| {
+        let mut item = print_to_json(print, file_version);
+        item["index"] = (*index).into();
+        item
+    }).collect::<Vec<_>>().into()
+}
+
+fn deconstruction_plan_to_json(plan: &DeconstructionPlan, file_version: Version) -> Value {
+    let mut settings = Map::new();
+    if !plan.description.is_empty() {
+        settings.insert("description".into(), plan.description.as_ref().into());
+    }
+    insert_icons(&mut settings, &plan.icons);
+    settings.insert("entity_filter_mode".into(), filter_mode_to_json(&plan.entity_filter_mode).into());
+    if !plan.entity_filters.is_empty() {
+        settings.insert("entity_filters".into(), filters_to_json(&plan.entity_filters));
+    }
+    if plan.trees_and_rocks_only {
+        settings.insert("trees_and_rocks_only".into(), true.into());
+    }
+    settings.insert("tile_filter_mode".into(), filter_mode_to_json(&plan.tile_filter_mode).into());
+    settings.insert("tile_selection_mode".into(), match plan.tile_selection_mode {
+        TileSelectionMode::Normal => 0,
+        TileSelectionMode::Always => 1,
+        TileSelectionMode::Never => 2,
+        TileSelectionMode::Only => 3,
+    }.into());
+    if !plan.tile_filters.is_empty() {
+        let filters = plan.tile_filters.iter()
+            .map(|(index, name)| json!({ "index": index + 1, "name": name })).collect::<Vec<_>>();
+        settings.insert("tile_filters".into(), filters.into());
+    }
+
+    json!({
+        "item": "deconstruction-planner",
+        "label": plan.label,
+        "settings": settings,
+        "version": version_to_json(file_version),
+    })
+}
+
+fn upgrade_plan_to_json(plan: &UpgradePlan, file_version: Version) -> Value {
+    let mut settings = Map::new();
+    if !plan.description.is_empty() {
+        settings.insert("description".into(), plan.description.as_ref().into());
+    }
+    insert_icons(&mut settings, &plan.icons);
+    let kind = |mapping: &UpgradeMapping| match mapping.kind {
+        UpgradeKind::Entity => "entity",
+        UpgradeKind::Item => "item",
+    };
+    let mappers = plan.mappings.iter().filter(|(_, mapping)| !mapping.is_empty()).map(|(index, mapping)| {
+        let mut item = json!({ "index": index });
+        if let Some((name, quality)) = &mapping.from {
+            item["from"] = json!({ "type": kind(mapping), "name": name });
+            if let Some(quality) = quality {
+                item["from"]["quality"] = quality.quality.as_ref().into();
+                item["from"]["comparator"] = quality.comparator.symbol().into();
+            }
+        }
+        if let Some((name, quality)) = &mapping.to {
+            item["to"] = json!({ "type": kind(mapping), "name": name });
+            if let Some(quality) = quality {
+                item["to"]["quality"] = quality.as_ref().into();
+            }
+        }
+        item
+    }).collect::<Vec<_>>();
+    settings.insert("mappers".into(), mappers.into());
+
+    json!({
+        "item": "upgrade-planner",
+        "label": plan.label,
+        "settings": settings,
+        "version": version_to_json(file_version),
+    })
+}
+
+// filter index is 1 based
+fn filters_to_json(filters: &[(usize, EntityFilter)]) -> Value {
+    filters.iter().map(|(index, filter)| filter_to_json(*index, filter)).collect::<Vec<_>>().into()
+}
+
+fn filter_to_json(index: usize, filter: &EntityFilter) -> Value {
+    let mut item = json!({ "index": index + 1, "name": filter.name });
+    if let Some(quality) = &filter.quality {
+        item["quality"] = quality.quality.as_ref().into();
+        item["comparator"] = quality.comparator.symbol().into();
+    }
+    item
+}
+
+// request_from_buffers is in entity, not in request_filters
+fn insert_logistic_request(result: &mut Map<String, Value>, request: &LogisticRequest) {
+    let mut request_filters = logistic_sections_to_json(&request.sections);
+    if request.trash_unrequested {
+        request_filters["trash_not_requested"] = true.into();
+    }
+    result.insert("request_filters".into(), request_filters);
+    if request.request_from_buffers {
+        result.insert("request_from_buffers".into(), true.into());
+    }
+}
+
+fn filter_mode_to_json(mode: &FilterMode) -> u8 {
+    match mode {
+        FilterMode::Whitelist => 0,
+        FilterMode::Blacklist => 1,
+    }
+}
+
+// default direction north is omitted
+fn insert_direction(result: &mut Map<String, Value>, direction: usize) {
+    if direction != 0 {
+        result.insert("direction".into(), direction.into());
+    }
+}
+
+fn circuit_condition_to_json(condition: &CircuitCondition) -> Value {
+    let mut result = Map::new();
+    if let Some(signal) = &condition.first_signal {
+        result.insert("first_signal".into(), signal_to_json(signal));
+    }
+    result.insert("comparator".into(), condition.comparator.symbol().into());
+    match &condition.second {
+        ConditionOperand::Constant(constant) => { result.insert("constant".into(), (*constant).into()); },
+        ConditionOperand::Signal(Some(signal)) => { result.insert("second_signal".into(), signal_to_json(signal)); },
+        ConditionOperand::Signal(None) => {},
+    }
+    result.into()
+}
+
+// empty signal is omitted
+fn insert_operand(result: &mut Map<String, Value>, signal_key: &str, constant_key: &str, operand: &ConditionOperand) {
+    match operand {
+        ConditionOperand::Constant(constant) => { result.insert(constant_key.into(), (*constant).into()); },
+        ConditionOperand::Signal(Some(signal)) => { result.insert(signal_key.into(), signal_to_json(signal)); },
+        ConditionOperand::Signal(None) => {},
+    }
+}
+
+// omitted when reading both
+fn insert_wire_networks(result: &mut Map<String, Value>, key: &str, networks: &WireNetworks) {
+    if *networks != WireNetworks::default() {
+        result.insert(key.into(), json!({ "red": networks.red, "green": networks.green }));
+    }
+}
+
+// section and filter index are 1 based, signal fields are flattened into filter
+fn logistic_sections_to_json(sections: &[LogisticSection]) -> Value {
+    let sections = sections.iter().enumerate().map(|(index, section)| {
+        let mut result = Map::new();
+        result.insert("index".into(), (index + 1).into());
+        let filters = section.filters.iter().map(|(index, filter)| {
+            let mut item = signal_to_json(&filter.signal);
+            item["index"] = (index + 1).into();
+            if let Some(quality) = &filter.quality {
+                item["quality"] = quality.as_ref().into();
+                item["comparator"] = "=".into();
+            }
+            item["count"] = filter.count.into();
+            if let Some(max) = filter.max {
+                item["max_count"] = max.into();
+            }
+            item
+        }).collect::<Vec<_>>();
+        if !filters.is_empty() {
+            result.insert("filters".into(), filters.into());
+        }
+        if !section.group.is_empty() {
+            result.insert("group".into(), section.group.as_ref().into());
+        }
+        if !section.active {
+            result.insert("active".into(), false.into());
+        }
+        result.into()
+    }).collect::<Vec<Value>>();
+    json!({ "sections": sections })
+}
+
+// item signal does not have type field in 2.0
+fn signal_to_json(signal: &Signal) -> Value {
+    match signal.kind {
+        SignalKind::Item => json!({ "name": signal.name }),
+        SignalKind::Fluid => json!({ "type": "fluid", "name": signal.name }),
+        SignalKind::Virtual => json!({ "type": "virtual", "name": signal.name }),
+    }
+}
+
+// icon index is 1 based
+fn insert_icons(result: &mut Map<String, Value>, icons: &[Option<Signal>]) {
+    let icons = icons.iter().enumerate()
+        .filter_map(|(index, icon)| icon.as_ref().map(|signal| json!({ "index": index + 1, "signal": signal_to_json(signal) })))
+        .collect::<Vec<_>>();
+    if !icons.is_empty() {
+        result.insert("icons".into(), icons.into());
+    }
+}
+
+// json to print
+
+// the json object wrapped by print type name
+pub fn print_from_json(value: &Value) -> anyhow::Result<Print<'_>> {
+    if let Some(blueprint) = value.get("blueprint") {
+        Ok(Print::Blueprint(blueprint_from_json(blueprint)?))
+    } else if let Some(book) = value.get("blueprint_book") {
+        Ok(Print::BlueprintBook(book_from_json(book)?))
+    } else if let Some(plan) = value.get("deconstruction_planner") {
+        Ok(Print::DeconstructionPlan(deconstruction_plan_from_json(plan)?))
+    } else if let Some(plan) = value.get("upgrade_planner") {
+        Ok(Print::UpgradePlan(upgrade_plan_from_json(plan)?))
+    } else {
+        bail!("unknown print type, expect blueprint, blueprint_book, deconstruction_planner or upgrade_planner")
+    }
+}
+
+// library of the only print in exchange string, so commands work on string same as on library file,
+// print version is used as file version, json use names directly so names are empty
+pub fn library_from_json(value: &Value) -> anyhow::Result<BlueprintLibrary<'_>> {
+    let print = print_from_json(value)?;
+    let version = ["blueprint", "blueprint_book", "deconstruction_planner", "upgrade_planner"].into_iter()
+        .find_map(|key| value.get(key)).and_then(|print| print.get("version")).and_then(Value::as_u64).map(version_from_json).ok_or_else(|| anyhow!("missing print version"))?;
+    Ok(BlueprintLibrary{ file_version: version, migrations: Vec::new(), names: Names::new(), mysterious_byte: 0, generation_counter: 0,
+        file_timestamp: DateTime::UNIX_EPOCH, print_count: 1, prints: vec![(0, print)], skipped: Vec::new() })
+}
+
+// json format does not have generation and migrations, they are 0 and empty
+fn blueprint_from_json(value: &Value) -> anyhow::Result<Blueprint<'_>> {
+    let label = get_optional_str(value, "label")?;
+    let version = version_from_json(get_u64(value, "version")?);
+    let description = get_optional_str(value, "description")?;
+    let icons = icons_from_json(value)?;
+    let snap_to_grid = match value.get("snap-to-grid") {
+        Some(size) => {
+            let size = (get_u64(size, "x")? as u32, get_u64(size, "y")? as u32);
+            let absolute = match value.get("position-relative-to-grid") {
+                Some(absolute) if value.get("absolute-snapping").and_then(Value::as_bool) == Some(true) =>
+                    (get_u64(absolute, "x")? as u32, get_u64(absolute, "y")? as u32),
+                _ => (0, 0),
+            };
+            Some(SnapToGrid{ size, absolute })
+        },
+        None => None,
+    };
+    let mut entities = get_optional_array(value, "entities")?.iter().map(entity_from_json).collect::<anyhow::Result<Vec<_>>>()?;
+    for schedule in get_optional_array(value, "schedules")? {
+        let locomotives = get_optional_array(schedule, "locomotives")?;
+        let schedule = schedule_from_json(schedule.get("schedule").unwrap_or(&Value::Null))?;
+        for entity_number in locomotives {
+            let entity_number = entity_number.as_u64().ok_or_else(|| anyhow!("invalid locomotive {entity_number}"))? as usize;
+            match entities.iter_mut().find(|entity| entity.entity_id == entity_number).map(|entity| &mut entity.kind) {
+                Some(EntityKind::Locomotive(locomotive)) => locomotive.schedule = Some(schedule.clone()),
+                _ => bail!("schedule for unknown locomotive {entity_number}"),
+            }
+        }
+    }
+    let wires = get_optional_array(value, "wires")?.iter().map(|wire| {
+        let end = |offset: usize| -> anyhow::Result<(usize, WireConnector)> {
+            let entity_number = wire.get(offset).and_then(Value::as_u64).ok_or_else(|| anyhow!("invalid wire {wire}"))? as usize;
+            let entity_index = entities.iter().position(|entity| entity.entity_id == entity_number)
+                .ok_or_else(|| anyhow!("wire to unknown entity number {entity_number}"))?;
+            let connector = wire.get(offset + 1).and_then(Value::as_u64).and_then(|id| WireConnector::from_id(id as u8))
+                .ok_or_else(|| anyhow!("invalid wire connector in {wire}"))?;
+            Ok((entity_index, connector))
+        };
+        Ok(Wire::new(end(0)?, end(2)?))
+    }).collect::<anyhow::Result<Vec<_>>>()?;
+    let wires = wires_from_json_1_1(get_optional_array(value, "entities")?, &entities, wires)?;
+    let tiles = get_optional_array(value, "tiles")?.iter().map(|tile| {
+        let position = tile.get("position").ok_or_else(|| anyhow!("missing tile position"))?;
+        Ok((get_str(tile, "name")?, (get_f64(position, "x")? as i32, get_f64(position, "y")? as i32)))
+    }).collect::<anyhow::Result<_>>()?;
+    let parameters = get_optional_array(value, "parameters")?.iter().map(parameter_from_json).collect::<anyhow::Result<_>>()?;
+    Ok(Blueprint{ generation: 0, label, version, migrations: Vec::new(), description, icons, snap_to_grid, entities, tiles,
+        wires, parameters })
+}
+
+// missing string is not set, missing bool is false
+fn parameter_from_json(value: &Value) -> anyhow::Result<BlueprintParameter<'_>> {
+    let optional_str = |key: &str| value.get(key).and_then(Value::as_str).map(Cow::Borrowed);
+    let kind = match value.get("type").and_then(Value::as_str) {
+        Some("id") => ParameterKind::Id{
+            id: get_str(value, "id")?,
+            quality_condition: value.get("quality-condition").map(quality_condition_from_json).transpose()?.flatten(),
+            ingredient_of: optional_str("ingredient-of"),
+        },
+        Some("number") => ParameterKind::Number{
+            number: get_str(value, "number")?,
+            variable: optional_str("variable"),
+            formula: optional_str("formula"),
+            dependent: value.get("dependent").and_then(Value::as_bool).unwrap_or(false),
+        },
+        other => bail!("invalid parameter type {other:?}"),
+    };
+    Ok(BlueprintParameter{
+        name: get_optional_str(value, "name")?,
+        not_parametrised: value.get("not-parametrised").and_then(Value::as_bool).unwrap_or(false),
+        kind,
+    })
+}
+
+// 1.1 circuit connections by circuit id, pole neighbours and power switch Cu0/Cu1 connections,
+// the other end of copper connection is always pole, connection is in both ends but only kept once
+fn wires_from_json_1_1(values: &[Value], entities: &[BlueprintEntity], mut wires: Vec<Wire>) -> anyhow::Result<Vec<Wire>> {
+    let entity_index = |entity_number: Option<u64>| entity_number
+        .and_then(|entity_number| entities.iter().position(|entity| entity.entity_id == entity_number as usize))
+        .ok_or_else(|| anyhow!("connection to unknown entity {entity_number:?}"));
+    for (index, value) in values.iter().enumerate() {
+        for (circuit_id, red) in [(1, true), (1, false), (2, true), (2, false)] {
+            let pointer = format!("/connections/{circuit_id}/{}", if red { "red" } else { "green" });
+            for connection in value.pointer(&pointer).and_then(Value::as_array).into_iter().flatten() {
+                let target_circuit_id = connection.get("circuit_id").and_then(Value::as_u64).unwrap_or(1) as usize;
+                let (Some(from), Some(to)) = (WireConnector::from_circuit(red, circuit_id), WireConnector::from_circuit(red, target_circuit_id)) else {
+                    bail!("invalid circuit id in {connection}");
+                };
+                let wire = Wire::new((index, from), (entity_index(connection.get("entity_id").and_then(Value::as_u64))?, to));
+                if !wires.contains(&wire) {
+                    wires.push(wire);
+                }
+            }
+        }
+        let neighbours = get_optional_array(value, "neighbours")?.iter().map(Value::as_u64);
+        let left = value.pointer("/connections/Cu0").and_then(Value::as_array).into_iter().flatten()
+            .map(|connection| connection.get("entity_id").and_then(Value::as_u64));
+        let right = value.pointer("/connections/Cu1").and_then(Value::as_array).into_iter().flatten()
+            .map(|connection| connection.get("entity_id").and_then(Value::as_u64));
+        let connections = neighbours.chain(left).map(|target| (WireConnector::Copper, target))
+            .chain(right.map(|target| (WireConnector::RightCopper, target)));
+        for (connector, target) in connections {
+            let wire = Wire::new((index, connector), (entity_index(target)?, WireConnector::Copper));
+            if !wires.contains(&wire) {
+                wires.push(wire);
+            }
+        }
+    }
+    Ok(wires)
+}
+
+// entity id is set to entity number, so Blueprint::entity_number still works
+fn entity_from_json(value: &Value) -> anyhow::Result<BlueprintEntity<'_>> {
+    let name = get_str(value, "name")?;
+    let entity_id = get_u64(value, "entity_number")? as usize;
+    let position = value.get("position").ok_or_else(|| anyhow!("missing position"))?;
+    let position = (get_f64(position, "x")?, get_f64(position, "y")?);
+    let direction = value.get("direction").and_then(Value::as_u64).unwrap_or(0) as usize;
+
+    let kind = match name.as_ref() {
+        "roboport" => {
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let signal = |key| optional_signal_from_json(behavior.get(key));
+            EntityKind::Roboport(Roboport{
+                circuit_connections: None,
+                read_logistics: behavior.get("read_logistics").and_then(Value::as_bool).unwrap_or(true),
+                read_robot_stats: behavior.get("read_robot_stats").and_then(Value::as_bool).unwrap_or(false),
+                available_logistic_output_signal: signal("available_logistic_output_signal")?,
+                total_logistic_output_signal: signal("total_logistic_output_signal")?,
+                available_construction_output_signal: signal("available_construction_output_signal")?,
+                total_construction_output_signal: signal("total_construction_output_signal")?,
+                roboport_count_output_signal: signal("roboport_count_output_signal")?,
+            })
+        },
+        "transport-belt" | "fast-transport-belt" | "express-transport-belt" | "turbo-transport-belt" => {
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let circuit_enabled = behavior.get("circuit_enabled").and_then(Value::as_bool).unwrap_or(false);
+            let read_contents = behavior.get("circuit_read_hand_contents").and_then(Value::as_bool).unwrap_or(false);
+            EntityKind::TransportBelt(TransportBelt{
+                tier: BeltTier::from_name(&name),
+                direction,
+                circuit_connections: None,
+                circuit_condition: if circuit_enabled {
+                    Some(circuit_condition_from_json(behavior.get("circuit_condition").unwrap_or(&Value::Null))?)
+                } else { None },
+                read_contents: if read_contents {
+                    Some(match behavior.get("circuit_contents_read_mode").and_then(Value::as_u64).unwrap_or(0) {
+                        0 => BeltReadMode::Pulse,
+                        1 => BeltReadMode::Hold,
+                        2 => BeltReadMode::EntireBeltHold,
+                        other => bail!("invalid circuit_contents_read_mode {other}"),
+                    })
+                } else { None },
+            })
+        },
+        "underground-belt" | "fast-underground-belt" | "express-underground-belt" | "turbo-underground-belt" =>
+            EntityKind::UndergroundBelt(UndergroundBelt{
+                tier: BeltTier::from_name(&name), direction, output: get_optional_str(value, "type")? == "output" }),
+        "splitter" | "fast-splitter" | "express-splitter" | "turbo-splitter" => {
+            let priority = |key| match value.get(key).and_then(Value::as_str) {
+                None => Ok(SplitterPriority::None),
+                Some("left") => Ok(SplitterPriority::Left),
+                Some("right") => Ok(SplitterPriority::Right),
+                Some(other) => Err(anyhow!("invalid {key} {other}")),
+            };
+            // 1.1 filter is item name, 2.0 filter is object with name and quality
+            let filter = match value.get("filter") {
+                None => None,
+                Some(Value::String(name)) => Some(Cow::Borrowed(name.as_str())),
+                Some(filter) => Some(get_str(filter, "name")?),
+            };
+            EntityKind::Splitter(Splitter{ tier: BeltTier::from_name(&name), direction,
+                input_priority: priority("input_priority")?, output_priority: priority("output_priority")?, filter })
+        },
+        "loader" | "fast-loader" | "express-loader" | "turbo-loader" =>
+            EntityKind::Loader(Loader{
+                tier: BeltTier::from_name(&name), direction, output: get_optional_str(value, "type")? == "output" }),
+        "burner-inserter" | "inserter" | "long-handed-inserter" | "fast-inserter" | "filter-inserter"
+        | "bulk-inserter" | "stack-filter-inserter" | "stack-inserter" => {
+            let tier = InserterTier::from_name(&name).unwrap();
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let flag = |key| behavior.get(key).and_then(Value::as_bool).unwrap_or(false);
+            let condition = |enabled, key| if enabled {
+                circuit_condition_from_json(behavior.get(key).unwrap_or(&Value::Null)).map(Some)
+            } else { Ok(None) };
+            // 1.1 filter inserters does not have use_filters
+            let use_filters = value.get("use_filters").and_then(Value::as_bool)
+                .unwrap_or(matches!(tier, InserterTier::Filter | InserterTier::StackFilter));
+            EntityKind::Inserter(Inserter{
+                tier,
+                direction,
+                circuit_connections: None,
+                use_filters,
+                filter_mode: match value.get("filter_mode").and_then(Value::as_str) {
+                    None | Some("whitelist") => FilterMode::Whitelist,
+                    Some("blacklist") => FilterMode::Blacklist,
+                    Some(other) => bail!("invalid filter_mode {other}"),
+                },
+                filters: if use_filters { filters_from_json(get_optional_array(value, "filters")?)? } else { Vec::new() },
+                stack_size_override: value.get("override_stack_size").and_then(Value::as_u64).map(|n| n as u8),
+                circuit_condition: condition(flag("circuit_enabled"), "circuit_condition")?,
+                logistic_condition: condition(flag("connect_to_logistic_network"), "logistic_condition")?,
+                read_hand_contents: if flag("circuit_read_hand_contents") {
+                    Some(match behavior.get("circuit_hand_read_mode").and_then(Value::as_u64).unwrap_or(0) {
+                        0 => HandReadMode::Pulse,
+                        1 => HandReadMode::Hold,
+                        other => bail!("invalid circuit_hand_read_mode {other}"),
+                    })
+                } else { None },
+                set_filters: flag("circuit_set_filters"),
+                set_stack_size: if flag("circuit_set_stack_size") {
+                    Some(optional_signal_from_json(behavior.get("stack_control_input_signal"))?)
+                } else { None },
+            })
+        },
+        "arithmetic-combinator" => {
+            let conditions = value.pointer("/control_behavior/arithmetic_conditions").unwrap_or(&Value::Null);
+            EntityKind::ArithmeticCombinator(ArithmeticCombinator{
+                direction,
+                input_connections: None,
+                output_connections: None,
+                first: operand_from_json(conditions, "first_signal", "first_constant")?,
+                operation: match conditions.get("operation").and_then(Value::as_str).unwrap_or("*") {
+                    "*" => ArithmeticOperation::Multiply,
+                    "/" => ArithmeticOperation::Divide,
+                    "+" => ArithmeticOperation::Add,
+                    "-" => ArithmeticOperation::Subtract,
+                    "%" => ArithmeticOperation::Modulo,
+                    "^" => ArithmeticOperation::Power,
+                    "<<" => ArithmeticOperation::LeftShift,
+                    ">>" => ArithmeticOperation::RightShift,
+                    "AND" => ArithmeticOperation::And,
+                    "OR" => ArithmeticOperation::Or,
+                    "XOR" => ArithmeticOperation::Xor,
+                    other => bail!("invalid arithmetic operation {other}"),
+                },
+                second: operand_from_json(conditions, "second_signal", "second_constant")?,
+                output_signal: optional_signal_from_json(conditions.get("output_signal"))?,
+            })
+        },
+        "decider-combinator" => {
+            let conditions = value.pointer("/control_behavior/decider_conditions").unwrap_or(&Value::Null);
+            EntityKind::DeciderCombinator(decider_combinator_from_json(conditions, direction)?)
+        },
+        "constant-combinator" => {
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let sections = match behavior.get("filters") {
+                // 1.1 signal list, each filter is { signal, count, index }
+                Some(filters) => vec![LogisticSection{ group: Cow::Borrowed(""), active: true,
+                    filters: filters.as_array().ok_or_else(|| anyhow!("invalid filters"))?.iter().map(|filter| Ok((
+                        (get_u64(filter, "index")? as usize).checked_sub(1).ok_or_else(|| anyhow!("invalid filter index 0"))?,
+                        LogisticFilter{ signal: signal_from_json(filter.get("signal").ok_or_else(|| anyhow!("missing signal"))?)?,
+                            quality: None, count: filter.get("count").and_then(Value::as_i64).unwrap_or(0) as i32, max: None },
+                    ))).collect::<anyhow::Result<_>>()? }],
+                None => logistic_sections_from_json(behavior.get("sections").unwrap_or(&Value::Null))?,
+            };
+            EntityKind::ConstantCombinator(ConstantCombinator{
+                direction,
+                circuit_connections: None,
+                enabled: behavior.get("is_on").and_then(Value::as_bool).unwrap_or(true),
+                sections,
+            })
+        },
+        "selector-combinator" => {
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let signal = |key| optional_signal_from_json(behavior.get(key));
+            let mode = match behavior.get("operation").and_then(Value::as_str).unwrap_or("select") {
+                "select" => SelectorMode::SelectInput{
+                    select_max: behavior.get("select_max").and_then(Value::as_bool).unwrap_or(true),
+                    index: operand_from_json(behavior, "index_signal", "index_constant")?,
+                },
+                "count" => SelectorMode::CountInputs{ output_signal: signal("count_signal")? },
+                "random" => SelectorMode::RandomInput{
+                    interval: behavior.get("random_update_interval").and_then(Value::as_u64).unwrap_or(0) as u32 },
+                "stack-size" => SelectorMode::StackSize,
+                "rocket-capacity" => SelectorMode::RocketCapacity,
+                "quality-filter" => SelectorMode::QualityFilter{
+                    quality: behavior.get("quality_filter").map(quality_condition_from_json).transpose()?.flatten() },
+                "quality-transfer" => SelectorMode::QualityTransfer{
+                    source: if behavior.get("select_quality_from_signal").and_then(Value::as_bool).unwrap_or(false) {
+                        QualitySource::Signal(signal("quality_source_signal")?)
+                    } else {
+                        let quality = behavior.get("quality_source_static").unwrap_or(&Value::Null);
+                        QualitySource::Static(get_optional_str(quality, "name")?)
+                    },
+                    destination_signal: signal("quality_destination_signal")?,
+                },
+                other => bail!("invalid selector operation {other}"),
+            };
+            EntityKind::SelectorCombinator(SelectorCombinator{ direction, mode })
+        },
+        "straight-rail" | "curved-rail" | "half-diagonal-rail" | "curved-rail-a" | "curved-rail-b"
+        | "elevated-straight-rail" | "elevated-half-diagonal-rail" | "elevated-curved-rail-a" | "elevated-curved-rail-b"
+        | "rail-ramp" | "rail-support" => EntityKind::Rail(Rail::from_name(&name, direction).unwrap()),
+        "rail-signal" | "rail-chain-signal" => {
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let flag = |key| behavior.get(key).and_then(Value::as_bool).unwrap_or(false);
+            let signal = |key| optional_signal_from_json(behavior.get(key));
+            EntityKind::RailSignal(RailSignal{
+                chain: name == "rail-chain-signal",
+                direction,
+                circuit_connections: None,
+                close_condition: if flag("circuit_close_signal") {
+                    Some(circuit_condition_from_json(behavior.get("circuit_condition").unwrap_or(&Value::Null))?)
+                } else { None },
+                read_signals: if flag("circuit_read_signal") {
+                    Some([signal("red_output_signal")?, signal("orange_output_signal")?, signal("green_output_signal")?,
+                        if name == "rail-chain-signal" { signal("blue_output_signal")? } else { None }])
+                } else { None },
+            })
+        },
+        "train-stop" => {
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let flag = |key| behavior.get(key).and_then(Value::as_bool).unwrap_or(false);
+            let optional_signal = |flag_key, key| if flag(flag_key) {
+                optional_signal_from_json(behavior.get(key)).map(Some)
+            } else { Ok(None) };
+            EntityKind::TrainStop(TrainStop{
+                direction,
+                circuit_connections: None,
+                station: get_optional_str(value, "station")?,
+                train_limit: value.get("manual_trains_limit").and_then(Value::as_u64).map(|limit| limit as u32),
+                priority: value.get("priority").and_then(Value::as_u64).unwrap_or(50) as u8,
+                enable_condition: if flag("circuit_enable_disable") {
+                    Some(circuit_condition_from_json(behavior.get("circuit_condition").unwrap_or(&Value::Null))?)
+                } else { None },
+                send_to_train: flag("send_to_train"),
+                read_from_train: flag("read_from_train"),
+                read_stopped_train: optional_signal("read_stopped_train", "train_stopped_signal")?,
+                set_trains_limit: optional_signal("set_trains_limit", "trains_limit_signal")?,
+                read_trains_count: optional_signal("read_trains_count", "trains_count_signal")?,
+                set_priority: optional_signal("set_priority", "priority_signal")?,
+            })
+        },
+        "wooden-chest" | "iron-chest" | "steel-chest" | "active-provider-chest" | "passive-provider-chest"
+        | "storage-chest" | "buffer-chest" | "requester-chest" | "logistic-chest-active-provider"
+        | "logistic-chest-passive-provider" | "logistic-chest-storage" | "logistic-chest-buffer" | "logistic-chest-requester" => {
+            let (container, legacy_name) = ContainerType::from_name(&name).unwrap();
+            EntityKind::Container(Container{
+                container,
+                legacy_name,
+                circuit_connections: None,
+                bar: value.get("bar").and_then(Value::as_u64).map(|bar| bar as u16),
+                filter: if container == ContainerType::Storage {
+                    filters_from_json(get_optional_array(value, "filters")?)?.into_iter().next().map(|(_, filter)| filter)
+                } else { None },
+                request: if container.has_request() { Some(logistic_request_from_json(value)?) } else { None },
+            })
+        },
+        "cargo-wagon" => {
+            let inventory = value.get("inventory").unwrap_or(&Value::Null);
+            EntityKind::CargoWagon(CargoWagon{
+                orientation: value.get("orientation").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+                bar: inventory.get("bar").and_then(Value::as_u64).map(|bar| bar as u16),
+                filters: filters_from_json(get_optional_array(inventory, "filters")?)?,
+            })
+        },
+        "small-electric-pole" | "medium-electric-pole" | "big-electric-pole" | "substation" => EntityKind::ElectricPole(ElectricPole{
+            pole: PoleType::from_name(&name).unwrap(),
+            circuit_connections: None,
+            // 1.1 neighbours are converted to blueprint wires
+            copper_connections: None,
+        }),
+        "power-switch" => {
+            let behavior = value.get("control_behavior").unwrap_or(&Value::Null);
+            let condition = |flag, key| -> anyhow::Result<_> {
+                Ok(if behavior.get(flag).and_then(Value::as_bool).unwrap_or(false) {
+                    Some(circuit_condition_from_json(behavior.get(key).unwrap_or(&Value::Null))?)
+                } else { None })
+            };
+            EntityKind::PowerSwitch(PowerSwitch{
+                circuit_connections: None,
+                copper_connections: None,
+                switch_state: value.get("switch_state").and_then(Value::as_bool).unwrap_or(false),
+                circuit_condition: condition("circuit_enable_disable", "circuit_condition")?,
+                logistic_condition: condition("connect_to_logistic_network", "logistic_condition")?,
+            })
+        },
+        "accumulator" => EntityKind::Accumulator(Accumulator{
+            circuit_connections: None,
+            output_signal: optional_signal_from_json(value.pointer("/control_behavior/output_signal"))?,
+        }),
+        "boiler" | "steam-engine" | "steam-turbine" | "solar-panel" | "nuclear-reactor" | "heat-exchanger"
+        | "heating-tower" | "fusion-reactor" | "fusion-generator" =>
+            EntityKind::PowerProducer(PowerProducer{ producer: PowerProducerType::from_name(&name).unwrap(), direction }),
+        "space-platform-hub" => EntityKind::SpacePlatformHub(SpacePlatformHub{ request: logistic_request_from_json(value)? }),
+        "locomotive" => EntityKind::Locomotive(Locomotive{
+            orientation: value.get("orientation").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+            // set later from blueprint schedules
+            schedule: None,
+        }),
+        "assembling-machine-1" | "assembling-machine-2" | "assembling-machine-3" | "chemical-plant" | "oil-refinery"
+        | "centrifuge" | "foundry" | "electromagnetic-plant" | "cryogenic-plant" | "biochamber" | "crusher" =>
+            EntityKind::CraftingMachine(CraftingMachine{
+                machine: CraftingMachineType::from_name(&name).unwrap(),
+                direction,
+                mirrored: value.get("mirror").and_then(Value::as_bool).unwrap_or(false),
+                recipe: value.get("recipe").map(|_| get_str(value, "recipe")).transpose()?,
+                recipe_quality: value.get("recipe_quality").map(|_| get_str(value, "recipe_quality")).transpose()?,
+            }),
+        // common fields are read below, 1.1 connections are converted to blueprint wires
+        _ => EntityKind::Unknown(UnknownEntity{
+            name: name.clone(),
+            fields: value.as_object().ok_or_else(|| anyhow!("invalid entity"))?.iter()
+                .filter(|(key, _)| !matches!(key.as_str(), "entity_number" | "name" | "position" | "quality" | "items" | "tags" | "connections" | "neighbours"))
+                .map(|(key, value)| (key.clone(), value.clone())).collect(),
+        }),
+    };
+
+    // 1.1 use name to count object, 2.0 use list of item id and inventory positions
+    let mut items = Vec::new();
+    match value.get("items") {
+        Some(Value::Object(map)) => for (name, count) in map {
+            items.push((Cow::Borrowed(name.as_str()), None, count.as_u64().ok_or_else(|| anyhow!("invalid item count"))? as usize));
+        },
+        Some(Value::Array(array)) => for item in array {
+            let id = item.get("id").ok_or_else(|| anyhow!("missing item id"))?;
+            let name = get_str(id, "name")?;
+            let quality = id.get("quality").and_then(Value::as_str).map(Cow::Borrowed);
+            let count = item.get("items").and_then(|v| v.get("in_inventory")).and_then(Value::as_array)
+                .map(|positions| positions.iter().map(|p| p.get("count").and_then(Value::as_u64).unwrap_or(1)).sum::<u64>())
+                .unwrap_or(0);
+            items.push((name, quality, count as usize));
+        },
+        Some(_) => bail!("invalid items"),
+        None => {},
+    }
+
+    let tags = match value.get("tags").map(tag_from_json).transpose()? {
+        Some(TagValue::Table(entries)) => entries,
+        Some(_) => bail!("invalid tags"),
+        None => Vec::new(),
+    };
+
+    let quality = value.get("quality").and_then(Value::as_str).map(Cow::Borrowed);
+    Ok(BlueprintEntity{ kind, position, entity_id, quality, items, tags })
+}
+
+// json object is table, its key order is not kept
+fn tag_from_json(value: &Value) -> anyhow::Result<TagValue<'_>> {
+    Ok(match value {
+        Value::Bool(value) => TagValue::Bool(*value),
+        Value::Number(value) => TagValue::Number(value.as_f64().ok_or_else(|| anyhow!("invalid tag number {value}"))?),
+        Value::String(value) => TagValue::String(Cow::Borrowed(value)),
+        Value::Array(values) => TagValue::List(values.iter().map(tag_from_json).collect::<anyhow::Result<_>>()?),
+        Value::Object(entries) => TagValue::Table(entries.iter()
+            .map(|(key, value)| Ok((Cow::Borrowed(key.as_str()), tag_from_json(value)?))).collect::<anyhow::Result<_>>()?),
+        Value::Null => bail!("invalid tag null"),
+    })
+}
+
+fn book_from_json(value: &Value) -> anyhow::Result<BlueprintBook<'_>> {
+    let label = get_optional_str(value, "label")?;
+    let description = get_optional_str(value, "description")?;
+    let icons = icons_from_json(value)?;
+    let active_index = value.get("active_index").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let mut prints = get_optional_array(value, "blueprints")?.iter()
+        .map(|item| Ok((get_u64(item, "index")? as usize, print_from_json(item)?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    prints.sort_by_key(|(index, _)| *index);
+    let print_count = prints.last().map(|(index, _)| index + 1).unwrap_or(0);
+    Ok(BlueprintBook{ generation: 0, label, description, icons, active_index, print_count, prints })
+}
+
+fn deconstruction_plan_from_json(value: &Value) -> anyhow::Result<DeconstructionPlan<'_>> {
+    let label = get_optional_str(value, "label")?;
+    let settings = value.get("settings").unwrap_or(&Value::Null);
+    let description = get_optional_str(settings, "description")?;
+    let icons = icons_from_json(settings)?;
+
+    let filter_mode = |key| match settings.get(key).and_then(Value::as_u64).unwrap_or(0) {
+        0 => Ok(FilterMode::Whitelist),
+        1 => Ok(FilterMode::Blacklist),
+        v => Err(anyhow!("invalid {key} {v}")),
+    };
+    let entity_filter_mode = filter_mode("entity_filter_mode")?;
+    let entity_filters = filters_from_json(get_optional_array(settings, "entity_filters")?)?;
+    // exchange string has no empty slots
+    let entity_filter_count = entity_filters.last().map(|(index, _)| index + 1).unwrap_or(0);
+    let trees_and_rocks_only = settings.get("trees_and_rocks_only").and_then(Value::as_bool).unwrap_or(false);
+
+    let tile_filter_mode = filter_mode("tile_filter_mode")?;
+    let tile_selection_mode = match settings.get("tile_selection_mode").and_then(Value::as_u64).unwrap_or(0) {
+        0 => TileSelectionMode::Normal,
+        1 => TileSelectionMode::Always,
+        2 => TileSelectionMode::Never,
+        3 => TileSelectionMode::Only,
+        v => bail!("invalid tile_selection_mode {v}"),
+    };
+    let mut tile_filters = get_optional_array(settings, "tile_filters")?.iter().map(|filter| {
+        let index = get_u64(filter, "index")? as usize;
+        if index == 0 { bail!("invalid filter index 0"); }
+        Ok((index - 1, get_str(filter, "name")?))
+    }).collect::<anyhow::Result<Vec<_>>>()?;
+    tile_filters.sort_by_key(|(index, _)| *index);
+    let tile_filter_count = tile_filters.last().map(|(index, _)| index + 1).unwrap_or(0);
+
+    Ok(DeconstructionPlan{ generation: 0, label, description, icons, entity_filter_mode, entity_filter_count, entity_filters,
+        trees_and_rocks_only, tile_filter_mode, tile_selection_mode, tile_filter_count, tile_filters })
+}
+
+fn upgrade_plan_from_json(value: &Value) -> anyhow::Result<UpgradePlan<'_>> {
+    let label = get_optional_str(value, "label")?;
+    let settings = value.get("settings").unwrap_or(&Value::Null);
+    let description = get_optional_str(settings, "description")?;
+    let icons = icons_from_json(settings)?;
+
+    let mut mappings = Vec::new();
+    for mapper in get_optional_array(settings, "mappers")? {
+        let from = mapper.get("from");
+        let to = mapper.get("to");
+        let kind = match from.or(to).map(|side| get_str(side, "type")).transpose()?.as_deref() {
+            Some("entity") | None => UpgradeKind::Entity,
+            Some("item") => UpgradeKind::Item,
+            Some(other) => bail!("invalid upgrade mapper type {other}"),
+        };
+        let from = from.map(|side| Ok::<_, anyhow::Error>((get_str(side, "name")?, quality_condition_from_json(side)?))).transpose()?;
+        let to = to.map(|side| Ok::<_, anyhow::Error>((get_str(side, "name")?, side.get("quality").and_then(Value::as_str).map(Cow::Borrowed)))).transpose()?;
+        mappings.push((get_u64(mapper, "index")? as usize, UpgradeMapping{ kind, from, to }));
+    }
+    mappings.sort_by_key(|(index, _)| *index);
+    // exchange string has no empty slots
+    let mapping_count = mappings.last().map(|(index, _)| index + 1).unwrap_or(0);
+
+    Ok(UpgradePlan{ generation: 0, label, description, icons, mapping_count, mappings })
+}
+
+fn filters_from_json(filters: &[Value]) -> anyhow::Result<Vec<(usize, EntityFilter<'_>)>> {
+    let mut filters = filters.iter().map(|filter| {
+        let index = get_u64(filter, "index")? as usize;
+        if index == 0 { bail!("invalid filter index 0"); }
+        Ok((index - 1, EntityFilter{ name: get_str(filter, "name")?, quality: quality_condition_from_json(filter)? }))
+    }).collect::<anyhow::Result<Vec<_>>>()?;
+    filters.sort_by_key(|(index, _)| *index);
+    Ok(filters)
+}
+
+// no quality means any quality, comparator default to equal
+fn quality_condition_from_json(value: &Value) -> anyhow::Result<Option<QualityCondition<'_>>> {
+    let Some(quality) = value.get("quality").and_then(Value::as_str).map(Cow::Borrowed) else { return Ok(None); };
+    Ok(Some(QualityCondition{ comparator: comparator_from_json(value, "=")?, quality }))
+}
+
+// missing comparator is "=" in quality condition but "<" in circuit condition
+fn comparator_from_json<'a>(value: &'a Value, default: &'a str) -> anyhow::Result<Comparator> {
+    Ok(match value.get("comparator").and_then(Value::as_str).unwrap_or(default) {
+        ">" => Comparator::GreaterThan,
+        "<" => Comparator::LessThan,
+        "=" => Comparator::Equal,
+        "≥" | ">=" => Comparator::GreaterThanOrEqual,
+        "≤" | "<=" => Comparator::LessThanOrEqual,
+        "≠" | "!=" => Comparator::NotEqual,
+        other => bail!("invalid comparator {other}"),
+    })
+}
+
+// constant is preferred over signal if both exist
+fn operand_from_json<'a>(value: &'a Value, signal_key: &str, constant_key: &str) -> anyhow::Result<ConditionOperand<'a>> {
+    Ok(match value.get(constant_key) {
+        Some(constant) => ConditionOperand::Constant(constant.as_i64().ok_or_else(|| anyhow!("invalid {constant_key}"))? as i32),
+        None => ConditionOperand::Signal(optional_signal_from_json(value.get(signal_key))?),
+    })
+}
+
+fn wire_networks_from_json(value: &Value, key: &str) -> WireNetworks {
+    match value.get(key) {
+        Some(networks) => WireNetworks{
+            red: networks.get("red").and_then(Value::as_bool).unwrap_or(true),
+            green: networks.get("green").and_then(Value::as_bool).unwrap_or(true),
+        },
+        None => WireNetworks::default(),
+    }
+}
+
+// 1.1 have one condition and one output directly in decider conditions
+fn decider_combinator_from_json(value: &Value, direction: usize) -> anyhow::Result<DeciderCombinator<'_>> {
+    let (conditions, outputs) = match value.get("conditions") {
+        None => (
+            vec![DeciderCondition{ join: ConditionJoin::Or, condition: circuit_condition_from_json(value)?,
+                first_networks: WireNetworks::default(), second_networks: WireNetworks::default() }],
+            vec![DeciderOutput{ signal: optional_signal_from_json(value.get("output_signal"))?,
+                copy_count_from_input: value.get("copy_count_from_input").and_then(Value::as_bool).unwrap_or(true),
+                constant: 1, networks: WireNetworks::default() }],
+        ),
+        Some(conditions) => (
+            conditions.as_array().ok_or_else(|| anyhow!("invalid decider conditions"))?.iter().map(|condition| Ok(DeciderCondition{
+                join: match condition.get("compare_type").and_then(Value::as_str).unwrap_or("or") {
+                    "or" => ConditionJoin::Or,
+                    "and" => ConditionJoin::And,
+                    other => bail!("invalid compare_type {other}"),
+                },
+                condition: circuit_condition_from_json(condition)?,
+                first_networks: wire_networks_from_json(condition, "first_signal_networks"),
+                second_networks: wire_networks_from_json(condition, "second_signal_networks"),
+            })).collect::<anyhow::Result<_>>()?,
+            get_optional_array(value, "outputs")?.iter().map(|output| Ok(DeciderOutput{
+                signal: optional_signal_from_json(output.get("signal"))?,
+                copy_count_from_input: output.get("copy_count_from_input").and_then(Value::as_bool).unwrap_or(true),
+                constant: output.get("constant").and_then(Value::as_i64).unwrap_or(1) as i32,
+                networks: wire_networks_from_json(output, "networks"),
+            })).collect::<anyhow::Result<_>>()?,
+        ),
+    };
+    Ok(DeciderCombinator{ direction, input_connections: None, output_connections: None, conditions, outputs })
+}
+
+// 1.1 schedule is the record list
+fn schedule_from_json(value: &Value) -> anyhow::Result<Schedule<'_>> {
+    if let Some(records) = value.as_array() {
+        return Ok(Schedule{ records: schedule_records_from_json(records)?, interrupts: Vec::new() });
+    }
+    Ok(Schedule{
+        records: schedule_records_from_json(get_optional_array(value, "records")?)?,
+        interrupts: get_optional_array(value, "interrupts")?.iter().map(|interrupt| Ok(ScheduleInterrupt{
+            name: get_optional_str(interrupt, "name")?,
+            inside_interrupt: interrupt.get("inside_interrupt").and_then(Value::as_bool).unwrap_or(false),
+            conditions: wait_conditions_from_json(get_optional_array(interrupt, "conditions")?)?,
+            targets: schedule_records_from_json(get_optional_array(interrupt, "targets")?)?,
+        })).collect::<anyhow::Result<_>>()?,
+    })
+}
+
+fn schedule_records_from_json(records: &[Value]) -> anyhow::Result<Vec<ScheduleRecord<'_>>> {
+    records.iter().map(|record| Ok(ScheduleRecord{
+        station: get_optional_str(record, "station")?,
+        temporary: record.get("temporary").and_then(Value::as_bool).unwrap_or(false),
+        wait_conditions: wait_conditions_from_json(get_optional_array(record, "wait_conditions")?)?,
+    })).collect()
+}
+
+fn wait_conditions_from_json(conditions: &[Value]) -> anyhow::Result<Vec<WaitCondition<'_>>> {
+    conditions.iter().map(|condition| {
+        let ticks = || condition.get("ticks").and_then(Value::as_u64).unwrap_or(0) as u32;
+        let circuit_condition = || circuit_condition_from_json(condition.get("condition").unwrap_or(&Value::Null));
+        let station = || get_optional_str(condition, "station");
+        let kind = match get_str(condition, "type")?.as_ref() {
+            "time" => WaitConditionKind::Time{ ticks: ticks() },
+            "inactivity" => WaitConditionKind::Inactivity{ ticks: ticks() },
+            "full" => WaitConditionKind::Full,
+            "empty" => WaitConditionKind::Empty,
+            "item_count" => WaitConditionKind::ItemCount(circuit_condition()?),
+            "circuit" => WaitConditionKind::Circuit(circuit_condition()?),
+            "robots_inactive" => WaitConditionKind::RobotsInactive,
+            "fluid_count" => WaitConditionKind::FluidCount(circuit_condition()?),
+            "passenger_present" => WaitConditionKind::PassengerPresent,
+            "passenger_not_present" => WaitConditionKind::PassengerNotPresent,
+            "fuel_item_count_all" => WaitConditionKind::FuelItemCountAll(circuit_condition()?),
+            "fuel_item_count_any" => WaitConditionKind::FuelItemCountAny(circuit_condition()?),
+            "fuel_full" => WaitConditionKind::FuelFull,
+            "destination_full_or_no_path" => WaitConditionKind::DestinationFullOrNoPath,
+            "specific_destination_full" => WaitConditionKind::SpecificDestinationFull(station()?),
+            "specific_destination_not_full" => WaitConditionKind::SpecificDestinationNotFull(station()?),
+            "at_station" => WaitConditionKind::AtStation(station()?),
+            "not_at_station" => WaitConditionKind::NotAtStation(station()?),
+            "damage_taken" => WaitConditionKind::DamageTaken{ damage: condition.get("damage").and_then(Value::as_u64).unwrap_or(0) as u32 },
+            other => bail!("unhandled wait condition type {other}"),
+        };
+        let join = match condition.get("compare_type").and_then(Value::as_str).unwrap_or("or") {
+            "or" => ConditionJoin::Or,
+            "and" => ConditionJoin::And,
+            other => bail!("invalid compare_type {other}"),
+        };
+        Ok(WaitCondition{ join, kind })
+    }).collect()
+}
+
+// 1.1 request_filters is the item request list, each filter is { index, name, count }
+fn logistic_request_from_json(value: &Value) -> anyhow::Result<LogisticRequest<'_>> {
+    let request_filters = value.get("request_filters").unwrap_or(&Value::Null);
+    let sections = match request_filters.as_array() {
+        Some(filters) => vec![LogisticSection{ group: Cow::Borrowed(""), active: true, filters: logistic_filters_from_json(filters)? }],
+        None => logistic_sections_from_json(request_filters)?,
+    };
+    Ok(LogisticRequest{
+        sections,
+        trash_unrequested: request_filters.get("trash_not_requested").and_then(Value::as_bool).unwrap_or(false),
+        request_from_buffers: value.get("request_from_buffers").and_then(Value::as_bool).unwrap_or(false),
+    })
+}
+
+// the object with sections array, filters without name are skipped
+fn logistic_sections_from_json(value: &Value) -> anyhow::Result<Vec<LogisticSection<'_>>> {
+    let mut sections = get_optional_array(value, "sections")?.iter().map(|section| {
+        Ok((section.get("index").and_then(Value::as_u64).unwrap_or(0), LogisticSection{
+            group: get_optional_str(section, "group")?,
+            active: section.get("active").and_then(Value::as_bool).unwrap_or(true),
+            filters: logistic_filters_from_json(get_optional_array(section, "filters")?)?,
+        }))
+    }).collect::<anyhow::Result<Vec<_>>>()?;
+    sections.sort_by_key(|(index, _)| *index);
+    Ok(sections.into_iter().map(|(_, section)| section).collect())
+}
+
+fn logistic_filters_from_json(filters: &[Value]) -> anyhow::Result<Vec<(usize, LogisticFilter<'_>)>> {
+    let mut filters = filters.iter().filter(|filter| filter.get("name").is_some()).map(|filter| {
+        let index = get_u64(filter, "index")? as usize;
+        if index == 0 { bail!("invalid filter index 0"); }
+        Ok((index - 1, LogisticFilter{
+            signal: signal_from_json(filter)?,
+            quality: filter.get("quality").map(|_| get_str(filter, "quality")).transpose()?,
+            count: filter.get("count").and_then(Value::as_i64).unwrap_or(0) as i32,
+            max: filter.get("max_count").and_then(Value::as_i64).map(|max| max as i32),
+        }))
+    }).collect::<anyhow::Result<Vec<_>>>()?;
+    filters.sort_by_key(|(index, _)| *index);
+    Ok(filters)
+}
+
+// constant is preferred over second signal if both exist
+fn circuit_condition_from_json(value: &Value) -> anyhow::Result<CircuitCondition<'_>> {
+    let first_signal = optional_signal_from_json(value.get("first_signal"))?;
+    let comparator = comparator_from_json(value, "<")?;
+    let second = operand_from_json(value, "second_signal", "constant")?;
+    Ok(CircuitCondition{ first_signal, comparator, second })
+}
+
+// missing or null signal is not set, 2.0 writes null for default signal cleared in game
+fn optional_signal_from_json(value: Option<&Value>) -> anyhow::Result<Option<Signal<'_>>> {
+    value.filter(|value| !value.is_null()).map(signal_from_json).transpose()
+}
+fn signal_from_json(value: &Value) -> anyhow::Result<Signal<'_>> {
+    let kind = match value.get("type").and_then(Value::as_str) {
+        None | Some("item") => SignalKind::Item,
+        Some("fluid") => SignalKind::Fluid,
+        Some("virtual") => SignalKind::Virtual,
+        Some(other) => bail!("unhandled signal type {other}"),
+    };
+    Ok(Signal{ kind, name: get_str(value, "name")? })
+}
+
+// icon index is 1 based, missing icon is empty slot
+fn icons_from_json(value: &Value) -> anyhow::Result<Vec<Option<Signal<'_>>>> {
+    let mut icons = Vec::new();
+    for icon in get_optional_array(value, "icons")? {
+        let index = get_u64(icon, "index")? as usize;
+        if index == 0 { bail!("invalid icon index 0"); }
+        if icons.len() < index {
+            icons.resize_with(index, || None);
+        }
+        icons[index - 1] = Some(signal_from_json(icon.get("signal").ok_or_else(|| anyhow!("missing icon signal"))?)?);
+    }
+    Ok(icons)
+}
+
+// strings borrow the json value
+fn get_str<'a>(value: &'a Value, key: &str) -> anyhow::Result<Cow<'a, str>> {
+    value.get(key).and_then(Value::as_str).map(Cow::Borrowed).ok_or_else(|| anyhow!("missing or invalid {key}"))
+}
+// missing string is empty string
+fn get_optional_str<'a>(value: &'a Value, key: &str) -> anyhow::Result<Cow<'a, str>> {
+    match value.get(key) {
+        Some(v) => v.as_str().map(Cow::Borrowed).ok_or_else(|| anyhow!("invalid {key}")),
+        None => Ok(Cow::Borrowed("")),
+    }
+}
+fn get_u64(value: &Value, key: &str) -> anyhow::Result<u64> {
+    value.get(key).and_then(Value::as_u64).ok_or_else(|| anyhow!("missing or invalid {key}"))
+}
+fn get_f64(value: &Value, key: &str) -> anyhow::Result<f64> {
+    value.get(key).and_then(Value::as_f64).ok_or_else(|| anyhow!("missing or invalid {key}"))
+}
+// missing array is empty array
+fn get_optional_array<'a>(value: &'a Value, key: &str) -> anyhow::Result<&'a [Value]> {
+    match value.get(key) {
+        Some(v) => v.as_array().map(Vec::as_slice).ok_or_else(|| anyhow!("invalid {key}")),
+        None => Ok(&[]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (file name, exchange string, decoded json if exists) of samples/*.txt
+    fn samples() -> Vec<(String, String, Option<Value>)> {
+        let directory = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("samples");
+        let mut paths = std::fs::read_dir(&directory).unwrap().map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "txt")).collect::<Vec<_>>();
+        paths.sort();
+        assert!(!paths.is_empty());
+        paths.into_iter().map(|path| {
+            let text = std::fs::read_to_string(&path).unwrap();
+            let json = std::fs::read_to_string(path.with_extension("json")).ok().map(|json| serde_json::from_str(&json).unwrap());
+            (path.file_name().unwrap().to_string_lossy().into_owned(), text, json)
+        }).collect()
+    }
+
+    // float text in samples may not be parsed to same f64, like 0.4941176474094391 and 0.49411764740943914
+    fn same_json(left: &Value, right: &Value) -> bool {
+        match (left, right) {
+            (Value::Number(left), Value::Number(right)) => (left.as_f64().unwrap() - right.as_f64().unwrap()).abs() < 1e-9,
+            (Value::Array(left), Value::Array(right)) => left.len() == right.len() && left.iter().zip(right).all(|(l, r)| same_json(l, r)),
+            (Value::Object(left), Value::Object(right)) => left.len() == right.len()
+                && left.iter().all(|(key, l)| right.get(key).is_some_and(|r| same_json(l, r))),
+            _ => left == right,
+        }
+    }
+
+    // blueprints in print and in its json, in same order
+    fn blueprints<'p, 'a>(print: &'p Print<'a>) -> Vec<&'p Blueprint<'a>> {
+        match print {
+            Print::Blueprint(blueprint) => vec![blueprint],
+            Print::BlueprintBook(book) => book.prints.iter().flat_map(|(_, print)| blueprints(print)).collect(),
+            Print::DeconstructionPlan(_) | Print::UpgradePlan(_) => Vec::new(),
+        }
+    }
+    fn blueprints_json(value: &Value) -> Vec<&Value> {
+        if let Some(blueprint) = value.get("blueprint") {
+            vec![blueprint]
+        } else if let Some(book) = value.get("blueprint_book") {
+            let mut items = get_optional_array(book, "blueprints").unwrap().iter().collect::<Vec<_>>();
+            items.sort_by_key(|item| item.get("index").and_then(Value::as_u64));
+            items.into_iter().flat_map(blueprints_json).collect()
+        } else { Vec::new() }
+    }
+
+    #[test]
+    fn import_samples() {
+        for (file_name, text, json) in samples() {
+            let decoded = decode(&text).unwrap();
+            if let Some(json) = json {
+                assert!(same_json(&decoded, &json), "{file_name} decoded json mismatch");
+            }
+            let print = print_from_json(&decoded).unwrap_or_else(|error| panic!("{file_name}: {error}"));
+
+            let (blueprints, blueprints_json) = (blueprints(&print), blueprints_json(&decoded));
+            assert_eq!(blueprints.len(), blueprints_json.len(), "{file_name}");
+            for (blueprint, json) in blueprints.into_iter().zip(blueprints_json) {
+                let entities = get_optional_array(json, "entities").unwrap();
+                assert_eq!(blueprint.entities.len(), entities.len(), "{file_name} {}", blueprint.label);
+                for (entity, json) in blueprint.entities.iter().zip(entities) {
+                    assert_eq!(entity.kind.name(), get_str(json, "name").unwrap(), "{file_name} {}", blueprint.label);
+                    assert_eq!(entity.entity_id as u64, get_u64(json, "entity_number").unwrap());
+                    assert_eq!(entity.position.0, get_f64(&json["position"], "x").unwrap());
+                    assert_eq!(entity.position.1, get_f64(&json["position"], "y").unwrap());
+                }
+                assert_eq!(blueprint.wires.len(), get_optional_array(json, "wires").unwrap().len(), "{file_name} {}", blueprint.label);
+            }
+        }
+    }
+
+    // fields not in model are not written, like request_filters of roboport, so only compare fields in model
+    #[test]
+    fn export_samples() {
+        for (file_name, text, json) in samples() {
+            let Some(json) = json else { continue };
+            let decoded = decode(&text).unwrap();
+            let exported = print_to_json(&print_from_json(&decoded).unwrap(), (2, 0, 28, 1));
+            let (blueprints, blueprints_json) = (blueprints_json(&exported), blueprints_json(&json));
+            assert_eq!(blueprints.len(), blueprints_json.len(), "{file_name}");
+            for (blueprint, json) in blueprints.into_iter().zip(blueprints_json) {
+                for key in ["label", "description", "icons", "snap-to-grid", "wires", "version"] {
+                    assert!(same_json(&blueprint[key], &json[key]), "{file_name} {key}");
+                }
+                let (entities, entities_json) = (get_optional_array(blueprint, "entities").unwrap(), get_optional_array(json, "entities").unwrap());
+                assert_eq!(entities.len(), entities_json.len(), "{file_name}");
+                for (entity, json) in entities.iter().zip(entities_json) {
+                    for key in ["entity_number", "name", "position", "direction", "quality", "items", "recipe", "station", "orientation"] {
+                        assert!(same_json(&entity[key], &json[key]), "{file_name} {key} {entity} {json}");
+                    }
+                }
+            }
+        }
+    }
+
+    // items are put in inventory of entity type, or inventory is left out
+    #[test]
+    fn item_inventories() {
+        let mut blueprint = Blueprint::new("items", (2, 0, 28, 1));
+        blueprint.add_entity(EntityKind::CraftingMachine(CraftingMachine{ machine: CraftingMachineType::AssemblingMachine3,
+            direction: 0, mirrored: false, recipe: None, recipe_quality: None }), (1.5, 1.5))
+            .add_quality_item("speed-module-2", "rare", 2).add_item("productivity-module", 1).add_item("iron-plate", 10);
+        blueprint.add_entity(EntityKind::Roboport(Roboport{ circuit_connections: None, read_logistics: true, read_robot_stats: false,
+            available_logistic_output_signal: None, total_logistic_output_signal: None, available_construction_output_signal: None,
+            total_construction_output_signal: None, roboport_count_output_signal: None }), (5.0, 5.0))
+            .add_item("construction-robot", 50).add_item("repair-pack", 20).add_item("logistic-robot", 10);
+        let print = Print::Blueprint(blueprint);
+
+        let json = print_to_json(&print, (2, 0, 28, 1));
+        let entities = &json["blueprint"]["entities"];
+        assert_eq!(entities[0]["items"], json!([
+            { "id": { "name": "speed-module-2", "quality": "rare" },
+                "items": { "in_inventory": [{ "inventory": 4, "stack": 0 }, { "inventory": 4, "stack": 1 }] } },
+            { "id": { "name": "productivity-module" }, "items": { "in_inventory": [{ "inventory": 4, "stack": 2 }] } },
+            { "id": { "name": "iron-plate" }, "items": {} },
+        ]));
+        assert_eq!(entities[1]["items"], json!([
+            { "id": { "name": "construction-robot" }, "items": { "in_inventory": [{ "inventory": 1, "stack": 0, "count": 50 }] } },
+            { "id": { "name": "repair-pack" }, "items": { "in_inventory": [{ "inventory": 2, "stack": 0, "count": 20 }] } },
+            { "id": { "name": "logistic-robot" }, "items": { "in_inventory": [{ "inventory": 1, "stack": 1, "count": 10 }] } },
+        ]));
+
+        // count is kept when inventory is known
+        let Print::Blueprint(imported) = print_from_json(&json).unwrap() else { panic!("not blueprint") };
+        assert_eq!(imported.entities[0].items, [("speed-module-2".into(), Some("rare".into()), 2), ("productivity-module".into(), None, 1),
+            ("iron-plate".into(), None, 0)]);
+        assert_eq!(imported.entities[1].items, [("construction-robot".into(), None, 50), ("repair-pack".into(), None, 20),
+            ("logistic-robot".into(), None, 10)]);
+    }
+
+    // slot index of library is kept as book index, so imported book have same slots
+    #[test]
+    fn library_book() {
+        let library = BlueprintLibrary{
+            file_version: (2, 0, 28, 1),
+            migrations: Vec::new(),
+            names: crate::name::Names::new(),
+            mysterious_byte: 0,
+            generation_counter: 1,
+            file_timestamp: chrono::DateTime::from_timestamp(1737417600, 0).unwrap(),
+            print_count: 4,
+            prints: vec![(1, Print::Blueprint(Blueprint::new("first", (2, 0, 28, 1)))), (3, Print::BlueprintBook(BlueprintBook::new("last")))],
+            skipped: Vec::new(),
+        };
+        let json = library_to_json(&library);
+        let decoded = decode(&encode_json(&json).unwrap()).unwrap();
+        assert_eq!(decoded, json);
+        let Print::BlueprintBook(book) = print_from_json(&decoded).unwrap() else { panic!("not book") };
+        assert_eq!(book.prints.iter().map(|(index, print)| (*index, print.label())).collect::<Vec<_>>(), [(1, "first"), (3, "last")]);
+        assert_eq!(book.print_count, 4);
+
+        let library = library_from_json(&decoded).unwrap();
+        assert_eq!((library.file_version, library.print_count, library.prints[0].0), ((2, 0, 28, 1), 1, 0));
+    }
+
+    // filter index is 1 based and may be in any order
+    #[test]
+    fn deconstruction_filters() {
+        let value = json!({ "deconstruction_planner": { "item": "deconstruction-planner", "settings": {
+            "tile_filters": [{ "index": 3, "name": "concrete" }, { "index": 1, "name": "landfill" }] } } });
+        let Print::DeconstructionPlan(plan) = print_from_json(&value).unwrap() else { panic!("not deconstruction plan") };
+        assert_eq!(plan.tile_filters, [(0, "landfill".into()), (2, "concrete".into())]);
+        assert_eq!(plan.tile_filter_count, 3);
+
+        let invalid = json!({ "deconstruction_planner": { "item": "deconstruction-planner", "settings": {
+            "tile_filters": [{ "index": 0, "name": "landfill" }] } } });
+        assert!(print_from_json(&invalid).is_err());
+    }
+
+    // fields of entity not handled yet are written back as is
+    #[test]
+    fn unknown_entity() {
+        let value = json!({ "blueprint": { "item": "blueprint", "version": version_to_json((2, 0, 28, 1)), "entities": [
+            { "entity_number": 1, "name": "small-lamp", "position": { "x": 0.5, "y": 0.5 }, "quality": "rare",
+                "color": { "r": 1, "g": 0, "b": 0, "a": 1 }, "always_on": true },
+            { "entity_number": 2, "name": "substation", "position": { "x": 3.0, "y": 1.0 } },
+            // machine and inserter from mod are not taken as vanilla ones
+            { "entity_number": 3, "name": "assembling-machine-4", "position": { "x": 6.5, "y": 1.5 }, "recipe": "iron-gear-wheel" },
+            { "entity_number": 4, "name": "kr-superior-inserter", "position": { "x": 8.5, "y": 1.5 }, "direction": 4 },
+        ], "wires": [[1, 5, 2, 5]] } });
+        let print = print_from_json(&value).unwrap();
+        let Print::Blueprint(blueprint) = &print else { panic!("not blueprint") };
+        let EntityKind::Unknown(lamp) = &blueprint.entities[0].kind else { panic!("not unknown") };
+        assert_eq!(lamp.name, "small-lamp");
+        assert_eq!(lamp.fields.keys().collect::<Vec<_>>(), ["always_on", "color"]);
+        assert_eq!(blueprint.entities[0].quality.as_deref(), Some("rare"));
+        assert!(matches!(&blueprint.entities[2].kind, EntityKind::Unknown(machine) if machine.name == "assembling-machine-4"));
+        assert!(matches!(&blueprint.entities[3].kind, EntityKind::Unknown(inserter) if inserter.name == "kr-superior-inserter"));
+        assert_eq!(print_to_json(&print, (2, 0, 28, 1)), value);
+    }
+
+    // 1.1 circuit connections are in both ends by circuit id, converted to wires
+    #[test]
+    fn circuit_connections_1_1() {
+        let value = json!({ "blueprint": { "item": "blueprint", "version": version_to_json((1, 1, 110, 0)), "entities": [
+            { "entity_number": 1, "name": "arithmetic-combinator", "position": { "x": 0.5, "y": 1 },
+                "connections": { "1": { "red": [{ "entity_id": 2 }] }, "2": { "green": [{ "entity_id": 3, "circuit_id": 1 }] } } },
+            { "entity_number": 2, "name": "small-lamp", "position": { "x": 2.5, "y": 0.5 },
+                "connections": { "1": { "red": [{ "entity_id": 1, "circuit_id": 1 }] } } },
+            { "entity_number": 3, "name": "medium-electric-pole", "position": { "x": 4.5, "y": 0.5 },
+                "connections": { "1": { "green": [{ "entity_id": 1, "circuit_id": 2 }] } } },
+        ] } });
+        let Print::Blueprint(blueprint) = print_from_json(&value).unwrap() else { panic!("not blueprint") };
+        assert_eq!(blueprint.wires, [
+            Wire::new((0, WireConnector::Red), (1, WireConnector::Red)),
+            Wire::new((0, WireConnector::OutputGreen), (2, WireConnector::Green)),
+        ]);
+        let EntityKind::Unknown(lamp) = &blueprint.entities[1].kind else { panic!("not unknown") };
+        assert!(lamp.fields.is_empty());
+
+        let invalid = json!({ "blueprint": { "item": "blueprint", "version": 0, "entities": [
+            { "entity_number": 1, "name": "small-lamp", "position": { "x": 0.5, "y": 0.5 },
+                "connections": { "1": { "red": [{ "entity_id": 2 }] } } },
+        ] } });
+        assert!(print_from_json(&invalid).is_err());
+    }
+
+    // wire have no direction, 2.0 wire from later entity is same as its reversed one
+    #[test]
+    fn wires_2_0() {
+        let value = json!({ "blueprint": { "item": "blueprint", "version": version_to_json((2, 0, 28, 1)), "entities": [
+            { "entity_number": 1, "name": "small-lamp", "position": { "x": 0.5, "y": 0.5 } },
+            { "entity_number": 2, "name": "small-lamp", "position": { "x": 2.5, "y": 0.5 },
+                "connections": { "1": { "red": [{ "entity_id": 1 }] } } },
+        ], "wires": [[2, 1, 1, 1]] } });
+        let Print::Blueprint(blueprint) = print_from_json(&value).unwrap() else { panic!("not blueprint") };
+        assert_eq!(blueprint.wires, [Wire::new((0, WireConnector::Red), (1, WireConnector::Red))]);
+    }
+}
@@ -0,0 +1,1803 @@
+// entity kind specific data of blueprint entity,
+// each entity family is one struct, tiers of same family are distinguished by tier field
+
+use std::borrow::Cow;
+use std::fmt;
+use serde_json::{Map, Value};
+use crate::blueprint_library::{owned, CircuitConnections, Comparator, EntityFilter, FilterMode, QualityCondition, Signal, WireConnector};
+
+pub enum EntityKind<'a> {
+    Roboport(Roboport<'a>),
+    TransportBelt(TransportBelt<'a>),
+    UndergroundBelt(UndergroundBelt),
+    Splitter(Splitter<'a>),
+    Loader(Loader),
+    Inserter(Inserter<'a>),
+    CraftingMachine(CraftingMachine<'a>),
+    ArithmeticCombinator(ArithmeticCombinator<'a>),
+    DeciderCombinator(DeciderCombinator<'a>),
+    ConstantCombinator(ConstantCombinator<'a>),
+    SelectorCombinator(SelectorCombinator<'a>),
+    Rail(Rail),
+    RailSignal(RailSignal<'a>),
+    TrainStop(TrainStop<'a>),
+    Locomotive(Locomotive<'a>),
+    Container(Container<'a>),
+    CargoWagon(CargoWagon<'a>),
+    SpacePlatformHub(SpacePlatformHub<'a>),
+    ElectricPole(ElectricPole),
+    PowerSwitch(PowerSwitch<'a>),
+    Accumulator(Accumulator<'a>),
+    PowerProducer(PowerProducer),
+    Unknown(UnknownEntity<'a>),
+}
+
+impl<'a> EntityKind<'a> {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Roboport(_) => "roboport",
+            Self::TransportBelt(item) => item.tier.select(["transport-belt", "fast-transport-belt", "express-transport-belt", "turbo-transport-belt"]),
+            Self::UndergroundBelt(item) => item.tier.select(["underground-belt", "fast-underground-belt", "express-underground-belt", "turbo-underground-belt"]),
+            Self::Splitter(item) => item.tier.select(["splitter", "fast-splitter", "express-splitter", "turbo-splitter"]),
+            Self::Loader(item) => item.tier.select(["loader", "fast-loader", "express-loader", "turbo-loader"]),
+            Self::Inserter(item) => item.tier.name(),
+            Self::CraftingMachine(item) => item.machine.name(),
+            Self::ArithmeticCombinator(_) => "arithmetic-combinator",
+            Self::DeciderCombinator(_) => "decider-combinator",
+            Self::ConstantCombinator(_) => "constant-combinator",
+            Self::SelectorCombinator(_) => "selector-combinator",
+            Self::Rail(item) => item.name(),
+            Self::RailSignal(item) => if item.chain { "rail-chain-signal" } else { "rail-signal" },
+            Self::TrainStop(_) => "train-stop",
+            Self::Locomotive(_) => "locomotive",
+            Self::Container(item) => item.container.name(item.legacy_name),
+            Self::CargoWagon(_) => "cargo-wagon",
+            Self::SpacePlatformHub(_) => "space-platform-hub",
+            Self::ElectricPole(item) => item.pole.name(),
+            Self::PowerSwitch(_) => "power-switch",
+            Self::Accumulator(_) => "accumulator",
+            Self::PowerProducer(item) => item.producer.name(),
+            Self::Unknown(item) => &item.name,
+        }
+    }
+
+    // ATTENTION NOT SAME 2/SA 1.1 have 8 directions, 2.0 have 16 directions, both 0 for north and clockwise,
+    // None for entity without direction, rolling stocks have orientation instead
+    pub fn direction(&self) -> Option<usize> {
+        Some(match self {
+            Self::TransportBelt(item) => item.direction,
+            Self::UndergroundBelt(item) => item.direction,
+            Self::Splitter(item) => item.direction,
+            Self::Loader(item) => item.direction,
+            Self::Inserter(item) => item.direction,
+            Self::CraftingMachine(item) => item.direction,
+            Self::ArithmeticCombinator(item) => item.direction,
+            Self::DeciderCombinator(item) => item.direction,
+            Self::ConstantCombinator(item) => item.direction,
+            Self::SelectorCombinator(item) => item.direction,
+            Self::Rail(item) => item.direction,
+            Self::RailSignal(item) => item.direction,
+            Self::TrainStop(item) => item.direction,
+            Self::PowerProducer(item) => item.direction,
+            Self::Roboport(_) | Self::Locomotive(_) | Self::Container(_) | Self::CargoWagon(_) | Self::SpacePlatformHub(_)
+            | Self::ElectricPole(_) | Self::PowerSwitch(_) | Self::Accumulator(_) | Self::Unknown(_) => return None,
+        })
+    }
+
+    // (item name, count) to place this entity, most entities are placed by item of same name,
+    // None for entity not placeable by item
+    pub fn placing_item(&self) -> Option<(&str, usize)> {
+        Some(match self {
+            // elevated rails are also placed by rail item
+            Self::Rail(item) => match item.shape {
+                RailShape::Ramp | RailShape::Support => (item.name(), 1),
+                // ATTENTION NOT SAME 2/SA 1.1 curved rail cost 4 rails, 2.0 curved rail cost 3 rails
+                RailShape::Curved => ("rail", 4),
+                RailShape::Straight => ("rail", 1),
+                RailShape::HalfDiagonal => ("rail", 2),
+                RailShape::CurvedA | RailShape::CurvedB => ("rail", 3),
+            },
+            // hub is created by space platform starter pack
+            Self::SpacePlatformHub(_) => return None,
+            _ => (self.name(), 1),
+        })
+    }
+
+    // ATTENTION NOT SAME 2/SA 1.1 circuit connections in entity by circuit id,
+    // that is 1 for input side or the only side, 2 for output side of combinators
+    pub fn circuit_connections(&self) -> Vec<(usize, &CircuitConnections)> {
+        let (input, output) = match self {
+            Self::Roboport(item) => (item.circuit_connections.as_ref(), None),
+            Self::TransportBelt(item) => (item.circuit_connections.as_ref(), None),
+            Self::Inserter(item) => (item.circuit_connections.as_ref(), None),
+            Self::ArithmeticCombinator(item) => (item.input_connections.as_ref(), item.output_connections.as_ref()),
+            Self::DeciderCombinator(item) => (item.input_connections.as_ref(), item.output_connections.as_ref()),
+            Self::ConstantCombinator(item) => (item.circuit_connections.as_ref(), None),
+            Self::RailSignal(item) => (item.circuit_connections.as_ref(), None),
+            Self::TrainStop(item) => (item.circuit_connections.as_ref(), None),
+            Self::Container(item) => (item.circuit_connections.as_ref(), None),
+            Self::ElectricPole(item) => (item.circuit_connections.as_ref(), None),
+            Self::PowerSwitch(item) => (item.circuit_connections.as_ref(), None),
+            Self::Accumulator(item) => (item.circuit_connections.as_ref(), None),
+            Self::UndergroundBelt(_) | Self::Splitter(_) | Self::Loader(_) | Self::CraftingMachine(_)
+            | Self::SelectorCombinator(_) | Self::Rail(_) | Self::Locomotive(_) | Self::CargoWagon(_)
+            | Self::SpacePlatformHub(_) | Self::PowerProducer(_) | Self::Unknown(_) => (None, None),
+        };
+        [(1, input), (2, output)].into_iter().filter_map(|(circuit_id, connections)| connections.map(|c| (circuit_id, c))).collect()
+    }
+
+    // ATTENTION NOT SAME 2/SA 1.1 copper connections in entity by entity id,
+    // the other end is always electric pole
+    pub fn copper_connections(&self) -> Vec<(WireConnector, &[usize])> {
+        match self {
+            Self::ElectricPole(ElectricPole{ copper_connections: Some(neighbours), .. }) => vec![(WireConnector::Copper, neighbours)],
+            Self::PowerSwitch(PowerSwitch{ copper_connections: Some([left, right]), .. }) =>
+                vec![(WireConnector::Copper, left), (WireConnector::RightCopper, right)],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn into_owned(self) -> EntityKind<'static> {
+        match self {
+            Self::Roboport(item) => EntityKind::Roboport(item.into_owned()),
+            Self::TransportBelt(item) => EntityKind::TransportBelt(item.into_owned()),
+            Self::UndergroundBelt(item) => EntityKind::UndergroundBelt(item),
+            Self::Splitter(item) => EntityKind::Splitter(item.into_owned()),
+            Self::Loader(item) => EntityKind::Loader(item),
+            Self::Inserter(item) => EntityKind::Inserter(item.into_owned()),
+            Self::CraftingMachine(item) => EntityKind::CraftingMachine(item.into_owned()),
+            Self::ArithmeticCombinator(item) => EntityKind::ArithmeticCombinator(item.into_owned()),
+            Self::DeciderCombinator(item) => EntityKind::DeciderCombinator(item.into_owned()),
+            Self::ConstantCombinator(item) => EntityKind::ConstantCombinator(item.into_owned()),
+            Self::SelectorCombinator(item) => EntityKind::SelectorCombinator(item.into_owned()),
+            Self::Rail(item) => EntityKind::Rail(item),
+            Self::RailSignal(item) => EntityKind::RailSignal(item.into_owned()),
+            Self::TrainStop(item) => EntityKind::TrainStop(item.into_owned()),
+            Self::Locomotive(item) => EntityKind::Locomotive(item.into_owned()),
+            Self::Container(item) => EntityKind::Container(item.into_owned()),
+            Self::CargoWagon(item) => EntityKind::CargoWagon(item.into_owned()),
+            Self::SpacePlatformHub(item) => EntityKind::SpacePlatformHub(item.into_owned()),
+            Self::ElectricPole(item) => EntityKind::ElectricPole(item),
+            Self::PowerSwitch(item) => EntityKind::PowerSwitch(item.into_owned()),
+            Self::Accumulator(item) => EntityKind::Accumulator(item.into_owned()),
+            Self::PowerProducer(item) => EntityKind::PowerProducer(item),
+            Self::Unknown(item) => EntityKind::Unknown(item.into_owned()),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for EntityKind<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Roboport(item) => write!(f, "{:?}", item),
+            Self::TransportBelt(item) => write!(f, "{:?}", item),
+            Self::UndergroundBelt(item) => write!(f, "{:?}", item),
+            Self::Splitter(item) => write!(f, "{:?}", item),
+            Self::Loader(item) => write!(f, "{:?}", item),
+            Self::Inserter(item) => write!(f, "{:?}", item),
+            Self::CraftingMachine(item) => write!(f, "{:?}", item),
+            Self::ArithmeticCombinator(item) => write!(f, "{:?}", item),
+            Self::DeciderCombinator(item) => write!(f, "{:?}", item),
+            Self::ConstantCombinator(item) => write!(f, "{:?}", item),
+            Self::SelectorCombinator(item) => write!(f, "{:?}", item),
+            Self::Rail(item) => write!(f, "{:?}", item),
+            Self::RailSignal(item) => write!(f, "{:?}", item),
+            Self::TrainStop(item) => write!(f, "{:?}", item),
+            Self::Locomotive(item) => write!(f, "{:?}", item),
+            Self::Container(item) => write!(f, "{:?}", item),
+            Self::CargoWagon(item) => write!(f, "{:?}", item),
+            Self::SpacePlatformHub(item) => write!(f, "{:?}", item),
+            Self::ElectricPole(item) => write!(f, "{:?}", item),
+            Self::PowerSwitch(item) => write!(f, "{:?}", item),
+            Self::Accumulator(item) => write!(f, "{:?}", item),
+            Self::PowerProducer(item) => write!(f, "{:?}", item),
+            Self::Unknown(item) => write!(f, "{:?}", item),
+        }
+    }
+}
+
+// enable/disable condition, also used by other circuit controlled entities
+#[derive(Clone)]
+pub struct CircuitCondition<'a> {
+    pub first_signal: Option<Signal<'a>>,
+    pub comparator: Comparator,
+    pub second: ConditionOperand<'a>,
+}
+
+#[derive(Clone)]
+pub enum ConditionOperand<'a> {
+    Signal(Option<Signal<'a>>),
+    Constant(i32),
+}
+
+impl<'a> CircuitCondition<'a> {
+    pub fn into_owned(self) -> CircuitCondition<'static> {
+        CircuitCondition{
+            first_signal: self.first_signal.map(Signal::into_owned),
+            comparator: self.comparator,
+            second: self.second.into_owned(),
+        }
+    }
+}
+
+impl<'a> ConditionOperand<'a> {
+    pub fn into_owned(self) -> ConditionOperand<'static> {
+        match self {
+            Self::Signal(signal) => ConditionOperand::Signal(signal.map(Signal::into_owned)),
+            Self::Constant(constant) => ConditionOperand::Constant(constant),
+        }
+    }
+}
+
+// like "Virtual signal-X > 5", empty signal is "_"
+impl<'a> fmt::Debug for CircuitCondition<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_signal(f, &self.first_signal)?;
+        write!(f, " {} {:?}", self.comparator.symbol(), self.second)
+    }
+}
+
+impl<'a> fmt::Debug for ConditionOperand<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Signal(signal) => write_signal(f, signal),
+            Self::Constant(constant) => write!(f, "{}", constant),
+        }
+    }
+}
+
+fn write_signal(f: &mut fmt::Formatter, signal: &Option<Signal>) -> fmt::Result {
+    match signal {
+        Some(signal) => write!(f, "{:?}", signal),
+        None => write!(f, "_"),
+    }
+}
+
+pub struct Roboport<'a> {
+    pub circuit_connections: Option<CircuitConnections>,
+    // control behaviors
+    pub read_logistics: bool,
+    pub read_robot_stats: bool,
+    pub available_logistic_output_signal: Option<Signal<'a>>,
+    pub total_logistic_output_signal: Option<Signal<'a>>,
+    pub available_construction_output_signal: Option<Signal<'a>>,
+    pub total_construction_output_signal: Option<Signal<'a>>,
+    // NEW in 2.0
+    pub roboport_count_output_signal: Option<Signal<'a>>,
+}
+
+impl<'a> Roboport<'a> {
+    pub fn into_owned(self) -> Roboport<'static> {
+        Roboport{
+            circuit_connections: self.circuit_connections,
+            read_logistics: self.read_logistics,
+            read_robot_stats: self.read_robot_stats,
+            available_logistic_output_signal: self.available_logistic_output_signal.map(Signal::into_owned),
+            total_logistic_output_signal: self.total_logistic_output_signal.map(Signal::into_owned),
+            available_construction_output_signal: self.available_construction_output_signal.map(Signal::into_owned),
+            total_construction_output_signal: self.total_construction_output_signal.map(Signal::into_owned),
+            roboport_count_output_signal: self.roboport_count_output_signal.map(Signal::into_owned),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Roboport<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        writeln!(f, "    read_logistics {}", self.read_logistics)?;
+        writeln!(f, "    read_robots_stats {}", self.read_robot_stats)?;
+        if let Some(signal) = &self.available_logistic_output_signal {
+            writeln!(f, "    available_logistic_output_signal {:?} {}", signal.kind, signal.name)?;
+        }
+        if let Some(signal) = &self.total_logistic_output_signal {
+            writeln!(f, "    total_logistic_output_signal {:?} {}", signal.kind, signal.name)?;
+        }
+        if let Some(signal) = &self.available_construction_output_signal {
+            writeln!(f, "    available_construction_output_signal {:?} {}", signal.kind, signal.name)?;
+        }
+        if let Some(signal) = &self.total_construction_output_signal {
+            writeln!(f, "    total_construction_output_signal {:?} {}", signal.kind, signal.name)?;
+        }
+        if let Some(signal) = &self.roboport_count_output_signal {
+            writeln!(f, "    roboport_count_output_signal {:?} {}", signal.kind, signal.name)?;
+        }
+        Ok(())
+    }
+}
+
+// transport belt, underground belt, splitter and loader have same tiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeltTier {
+    Normal,
+    Fast,
+    Express,
+    // NEW in space age
+    Turbo,
+}
+
+impl BeltTier {
+    // by entity name prefix, like fast-splitter
+    pub fn from_name(name: &str) -> Self {
+        if name.starts_with("fast-") {
+            Self::Fast
+        } else if name.starts_with("express-") {
+            Self::Express
+        } else if name.starts_with("turbo-") {
+            Self::Turbo
+        } else {
+            Self::Normal
+        }
+    }
+
+    // select from names in tier order
+    pub fn select(self, names: [&'static str; 4]) -> &'static str {
+        names[self as usize]
+    }
+}
+
+// how belt contents are read to circuit network
+#[derive(Debug, Clone, Copy)]
+pub enum BeltReadMode {
+    Pulse,
+    Hold,
+    // NEW in 2.0
+    EntireBeltHold,
+}
+
+pub struct TransportBelt<'a> {
+    pub tier: BeltTier,
+    pub direction: usize,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    // None for not enable/disable by circuit
+    pub circuit_condition: Option<CircuitCondition<'a>>,
+    // None for not read belt contents
+    pub read_contents: Option<BeltReadMode>,
+}
+
+impl<'a> TransportBelt<'a> {
+    pub fn into_owned(self) -> TransportBelt<'static> {
+        TransportBelt{
+            tier: self.tier,
+            direction: self.direction,
+            circuit_connections: self.circuit_connections,
+            circuit_condition: self.circuit_condition.map(CircuitCondition::into_owned),
+            read_contents: self.read_contents,
+        }
+    }
+}
+
+impl<'a> fmt::Debug for TransportBelt<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        if let Some(condition) = &self.circuit_condition {
+            writeln!(f, "    enable if {:?}", condition)?;
+        }
+        if let Some(mode) = &self.read_contents {
+            writeln!(f, "    read contents {:?}", mode)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct UndergroundBelt {
+    pub tier: BeltTier,
+    pub direction: usize,
+    pub output: bool, // false for input, true for output
+}
+
+impl fmt::Debug for UndergroundBelt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {} {}", self.direction, if self.output { "output" } else { "input" })
+    }
+}
+
+// splitter input and output priority side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitterPriority {
+    None,
+    Left,
+    Right,
+}
+
+pub struct Splitter<'a> {
+    pub tier: BeltTier,
+    pub direction: usize,
+    pub input_priority: SplitterPriority,
+    pub output_priority: SplitterPriority,
+    // filter item, filtered item goes to output priority side
+    pub filter: Option<Cow<'a, str>>,
+}
+
+impl<'a> Splitter<'a> {
+    pub fn into_owned(self) -> Splitter<'static> {
+        Splitter{
+            tier: self.tier,
+            direction: self.direction,
+            input_priority: self.input_priority,
+            output_priority: self.output_priority,
+            filter: self.filter.map(owned),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Splitter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        writeln!(f, "    priority input {:?} output {:?}", self.input_priority, self.output_priority)?;
+        if let Some(filter) = &self.filter {
+            writeln!(f, "    filter {}", filter)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Loader {
+    pub tier: BeltTier,
+    pub direction: usize,
+    pub output: bool, // false for input (load into container), true for output (unload from container)
+}
+
+impl fmt::Debug for Loader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {} {}", self.direction, if self.output { "output" } else { "input" })
+    }
+}
+
+// not really tier, but each inserter is one entity name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InserterTier {
+    Burner,
+    Basic,
+    LongHanded,
+    Fast,
+    // 1.1 only, in 2.0 all inserters can use filter
+    Filter,
+    // called stack inserter in 1.1
+    Bulk,
+    // 1.1 only
+    StackFilter,
+    // NEW in space age, the real stack inserter
+    Stack,
+}
+
+impl InserterTier {
+    // None for not an inserter name, like inserters from mods
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "burner-inserter" => Self::Burner,
+            "inserter" => Self::Basic,
+            "long-handed-inserter" => Self::LongHanded,
+            "fast-inserter" => Self::Fast,
+            "filter-inserter" => Self::Filter,
+            "bulk-inserter" => Self::Bulk,
+            "stack-filter-inserter" => Self::StackFilter,
+            "stack-inserter" => Self::Stack,
+            _ => return None,
+        })
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Burner => "burner-inserter",
+            Self::Basic => "inserter",
+            Self::LongHanded => "long-handed-inserter",
+            Self::Fast => "fast-inserter",
+            Self::Filter => "filter-inserter",
+            Self::Bulk => "bulk-inserter",
+            Self::StackFilter => "stack-filter-inserter",
+            Self::Stack => "stack-inserter",
+        }
+    }
+}
+
+// how hand contents are read to circuit network
+#[derive(Debug, Clone, Copy)]
+pub enum HandReadMode {
+    Pulse,
+    Hold,
+}
+
+pub struct Inserter<'a> {
+    pub tier: InserterTier,
+    pub direction: usize,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    // NEW in 2.0 any inserter can use filter, for 1.1 filter inserters this is always true
+    pub use_filters: bool,
+    pub filter_mode: FilterMode,
+    // (slot index, item filter)[], empty slots are not included
+    pub filters: Vec<(usize, EntityFilter<'a>)>,
+    pub stack_size_override: Option<u8>,
+    // None for not enable/disable by circuit
+    pub circuit_condition: Option<CircuitCondition<'a>>,
+    // None for not connect to logistic network
+    pub logistic_condition: Option<CircuitCondition<'a>>,
+    // None for not read hand contents
+    pub read_hand_contents: Option<HandReadMode>,
+    pub set_filters: bool,
+    // NEW in 2.0, None for not set stack size, Some(None) for empty signal
+    pub set_stack_size: Option<Option<Signal<'a>>>,
+}
+
+impl<'a> Inserter<'a> {
+    pub fn into_owned(self) -> Inserter<'static> {
+        Inserter{
+            tier: self.tier,
+            direction: self.direction,
+            circuit_connections: self.circuit_connections,
+            use_filters: self.use_filters,
+            filter_mode: self.filter_mode,
+            filters: self.filters.into_iter().map(|(index, filter)| (index, filter.into_owned())).collect(),
+            stack_size_override: self.stack_size_override,
+            circuit_condition: self.circuit_condition.map(CircuitCondition::into_owned),
+            logistic_condition: self.logistic_condition.map(CircuitCondition::into_owned),
+            read_hand_contents: self.read_hand_contents,
+            set_filters: self.set_filters,
+            set_stack_size: self.set_stack_size.map(|signal| signal.map(Signal::into_owned)),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Inserter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        if self.use_filters {
+            writeln!(f, "    filter mode {:?}", self.filter_mode)?;
+            for (index, filter) in &self.filters {
+                writeln!(f, "      #{} {:?}", index, filter)?;
+            }
+        }
+        if let Some(stack_size) = self.stack_size_override {
+            writeln!(f, "    stack size override {}", stack_size)?;
+        }
+        if let Some(condition) = &self.circuit_condition {
+            writeln!(f, "    enable if {:?}", condition)?;
+        }
+        if let Some(condition) = &self.logistic_condition {
+            writeln!(f, "    logistic enable if {:?}", condition)?;
+        }
+        if let Some(mode) = &self.read_hand_contents {
+            writeln!(f, "    read hand contents {:?}", mode)?;
+        }
+        if self.set_filters {
+            writeln!(f, "    set filters from circuit")?;
+        }
+        if let Some(signal) = &self.set_stack_size {
+            writeln!(f, "    set stack size from {:?}", signal)?;
+        }
+        Ok(())
+    }
+}
+
+// entities with recipe, each machine is one entity name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CraftingMachineType {
+    AssemblingMachine1,
+    AssemblingMachine2,
+    AssemblingMachine3,
+    ChemicalPlant,
+    OilRefinery,
+    Centrifuge,
+    // NEW in space age
+    Foundry,
+    ElectromagneticPlant,
+    CryogenicPlant,
+    Biochamber,
+    Crusher,
+}
+
+impl CraftingMachineType {
+    // None for not a crafting machine name, like machines from mods
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "assembling-machine-1" => Self::AssemblingMachine1,
+            "assembling-machine-2" => Self::AssemblingMachine2,
+            "assembling-machine-3" => Self::AssemblingMachine3,
+            "chemical-plant" => Self::ChemicalPlant,
+            "oil-refinery" => Self::OilRefinery,
+            "centrifuge" => Self::Centrifuge,
+            "foundry" => Self::Foundry,
+            "electromagnetic-plant" => Self::ElectromagneticPlant,
+            "cryogenic-plant" => Self::CryogenicPlant,
+            "biochamber" => Self::Biochamber,
+            "crusher" => Self::Crusher,
+            _ => return None,
+        })
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::AssemblingMachine1 => "assembling-machine-1",
+            Self::AssemblingMachine2 => "assembling-machine-2",
+            Self::AssemblingMachine3 => "assembling-machine-3",
+            Self::ChemicalPlant => "chemical-plant",
+            Self::OilRefinery => "oil-refinery",
+            Self::Centrifuge => "centrifuge",
+            Self::Foundry => "foundry",
+            Self::ElectromagneticPlant => "electromagnetic-plant",
+            Self::CryogenicPlant => "cryogenic-plant",
+            Self::Biochamber => "biochamber",
+            Self::Crusher => "crusher",
+        }
+    }
+}
+
+// modules are not here, they are in entity items like other inserted items, see BlueprintEntity::modules
+pub struct CraftingMachine<'a> {
+    pub machine: CraftingMachineType,
+    pub direction: usize,
+    // NEW in 2.0
+    pub mirrored: bool,
+    pub recipe: Option<Cow<'a, str>>,
+    // NEW in 2.0, None for not specified, that is normal quality
+    pub recipe_quality: Option<Cow<'a, str>>,
+}
+
+impl<'a> CraftingMachine<'a> {
+    pub fn into_owned(self) -> CraftingMachine<'static> {
+        CraftingMachine{
+            machine: self.machine,
+            direction: self.direction,
+            mirrored: self.mirrored,
+            recipe: self.recipe.map(owned),
+            recipe_quality: self.recipe_quality.map(owned),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for CraftingMachine<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}{}", self.direction, if self.mirrored { " mirrored" } else { "" })?;
+        if let Some(recipe) = &self.recipe {
+            match &self.recipe_quality {
+                Some(quality) => writeln!(f, "    recipe {} ({})", recipe, quality)?,
+                None => writeln!(f, "    recipe {}", recipe)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+// module item name to (module kind, tier), like productivity-module-3 to ("productivity", 3),
+// None for not a module
+pub fn module_tier(name: &str) -> Option<(&str, u8)> {
+    let (kind, tier) = name.split_once("-module")?;
+    let tier = match tier {
+        "" => 1,
+        "-2" => 2,
+        "-3" => 3,
+        _ => return None,
+    };
+    Some((kind, tier))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticOperation {
+    Multiply,
+    Divide,
+    Add,
+    Subtract,
+    Modulo,
+    Power,
+    LeftShift,
+    RightShift,
+    And,
+    Or,
+    Xor,
+}
+
+impl ArithmeticOperation {
+    // the symbol used in game and blueprint json format
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Multiply => "*",
+            Self::Divide => "/",
+            Self::Add => "+",
+            Self::Subtract => "-",
+            Self::Modulo => "%",
+            Self::Power => "^",
+            Self::LeftShift => "<<",
+            Self::RightShift => ">>",
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Xor => "XOR",
+        }
+    }
+}
+
+pub struct ArithmeticCombinator<'a> {
+    pub direction: usize,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity, combinators have input and output side
+    pub input_connections: Option<CircuitConnections>,
+    pub output_connections: Option<CircuitConnections>,
+    pub first: ConditionOperand<'a>,
+    pub operation: ArithmeticOperation,
+    pub second: ConditionOperand<'a>,
+    pub output_signal: Option<Signal<'a>>,
+}
+
+impl<'a> ArithmeticCombinator<'a> {
+    pub fn into_owned(self) -> ArithmeticCombinator<'static> {
+        ArithmeticCombinator{
+            direction: self.direction,
+            input_connections: self.input_connections,
+            output_connections: self.output_connections,
+            first: self.first.into_owned(),
+            operation: self.operation,
+            second: self.second.into_owned(),
+            output_signal: self.output_signal.map(Signal::into_owned),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for ArithmeticCombinator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        write_combinator_connections(f, &self.input_connections, &self.output_connections)?;
+        write!(f, "    {:?} {} {:?} -> ", self.first, self.operation.symbol(), self.second)?;
+        write_signal(f, &self.output_signal)?;
+        writeln!(f)
+    }
+}
+
+fn write_combinator_connections(f: &mut fmt::Formatter, input: &Option<CircuitConnections>, output: &Option<CircuitConnections>) -> fmt::Result {
+    if let Some(connections) = input {
+        writeln!(f, "    input circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+    }
+    if let Some(connections) = output {
+        writeln!(f, "    output circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+    }
+    Ok(())
+}
+
+// which input wire colors are read by a decider condition or output, default both
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireNetworks {
+    pub red: bool,
+    pub green: bool,
+}
+
+impl Default for WireNetworks {
+    fn default() -> Self {
+        Self{ red: true, green: true }
+    }
+}
+
+// only shown if not both, like "Virtual signal-X (red)"
+impl fmt::Display for WireNetworks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.red, self.green) {
+            (true, true) => Ok(()),
+            (true, false) => write!(f, " (red)"),
+            (false, true) => write!(f, " (green)"),
+            (false, false) => write!(f, " (none)"),
+        }
+    }
+}
+
+// how a decider condition is joined with previous conditions,
+// and has higher precedence than or, same as in game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConditionJoin {
+    Or,
+    And,
+}
+
+pub struct DeciderCondition<'a> {
+    // ignored for first condition
+    pub join: ConditionJoin,
+    pub condition: CircuitCondition<'a>,
+    // NEW in 2.0, 1.1 always read both
+    pub first_networks: WireNetworks,
+    pub second_networks: WireNetworks,
+}
+
+pub struct DeciderOutput<'a> {
+    pub signal: Option<Signal<'a>>,
+    // false for output constant
+    pub copy_count_from_input: bool,
+    // NEW in 2.0, 1.1 always output 1 if not copy count from input
+    pub constant: i32,
+    // NEW in 2.0, input networks to copy count from
+    pub networks: WireNetworks,
+}
+
+// 1.1 have exactly one condition and one output
+pub struct DeciderCombinator<'a> {
+    pub direction: usize,
+    // ATTENTION NOT SAME 2/SA see ArithmeticCombinator
+    pub input_connections: Option<CircuitConnections>,
+    pub output_connections: Option<CircuitConnections>,
+    pub conditions: Vec<DeciderCondition<'a>>,
+    pub outputs: Vec<DeciderOutput<'a>>,
+}
+
+impl<'a> DeciderCombinator<'a> {
+    pub fn into_owned(self) -> DeciderCombinator<'static> {
+        DeciderCombinator{
+            direction: self.direction,
+            input_connections: self.input_connections,
+            output_connections: self.output_connections,
+            conditions: self.conditions.into_iter().map(|condition| DeciderCondition{
+                join: condition.join,
+                condition: condition.condition.into_owned(),
+                first_networks: condition.first_networks,
+                second_networks: condition.second_networks,
+            }).collect(),
+            outputs: self.outputs.into_iter().map(|output| DeciderOutput{
+                signal: output.signal.map(Signal::into_owned),
+                copy_count_from_input: output.copy_count_from_input,
+                constant: output.constant,
+                networks: output.networks,
+            }).collect(),
+        }
+    }
+}
+
+// like
+//     if Item iron-plate (red) > 100
+//     and Virtual signal-X < Virtual signal-Y
+//     output Virtual signal-Y = input count
+impl<'a> fmt::Debug for DeciderCombinator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        write_combinator_connections(f, &self.input_connections, &self.output_connections)?;
+        for (index, condition) in self.conditions.iter().enumerate() {
+            let join = match (index, condition.join) {
+                (0, _) => "if",
+                (_, ConditionJoin::Or) => "or",
+                (_, ConditionJoin::And) => "and",
+            };
+            write!(f, "    {} ", join)?;
+            write_signal(f, &condition.condition.first_signal)?;
+            write!(f, "{} {} {:?}", condition.first_networks, condition.condition.comparator.symbol(), condition.condition.second)?;
+            if let ConditionOperand::Signal(_) = condition.condition.second {
+                write!(f, "{}", condition.second_networks)?;
+            }
+            writeln!(f)?;
+        }
+        for output in &self.outputs {
+            write!(f, "    output ")?;
+            write_signal(f, &output.signal)?;
+            if output.copy_count_from_input {
+                writeln!(f, " = input count{}", output.networks)?;
+            } else {
+                writeln!(f, " = {}", output.constant)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// signal and count in logistic section, count is the minimum for requests
+pub struct LogisticFilter<'a> {
+    pub signal: Signal<'a>,
+    // NEW in 2.0, None for not specified, that is normal quality
+    pub quality: Option<Cow<'a, str>>,
+    pub count: i32,
+    // NEW in 2.0, requests only, None for no maximum
+    pub max: Option<i32>,
+}
+
+impl<'a> fmt::Debug for LogisticFilter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.signal)?;
+        if let Some(quality) = &self.quality {
+            write!(f, " ({})", quality)?;
+        }
+        write!(f, " x {}", self.count)?;
+        if let Some(max) = self.max {
+            write!(f, "..{}", max)?;
+        }
+        Ok(())
+    }
+}
+
+// NEW in 2.0, group of signals, can be shared by name across entities
+pub struct LogisticSection<'a> {
+    // empty for not in a group
+    pub group: Cow<'a, str>,
+    pub active: bool,
+    // (slot index, filter)[], empty slots are not included
+    pub filters: Vec<(usize, LogisticFilter<'a>)>,
+}
+
+impl<'a> LogisticSection<'a> {
+    pub fn into_owned(self) -> LogisticSection<'static> {
+        LogisticSection{
+            group: owned(self.group),
+            active: self.active,
+            filters: self.filters.into_iter().map(|(index, filter)| (index, LogisticFilter{
+                signal: filter.signal.into_owned(),
+                quality: filter.quality.map(owned),
+                count: filter.count,
+                max: filter.max,
+            })).collect(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for LogisticSection<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "    section")?;
+        if !self.group.is_empty() {
+            write!(f, " {}", self.group)?;
+        }
+        writeln!(f, "{}", if self.active { "" } else { " (inactive)" })?;
+        for (index, filter) in &self.filters {
+            writeln!(f, "      #{} {:?}", index, filter)?;
+        }
+        Ok(())
+    }
+}
+
+// 1.1 have no section, its signals are one active section without group
+pub struct ConstantCombinator<'a> {
+    pub direction: usize,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    pub enabled: bool,
+    pub sections: Vec<LogisticSection<'a>>,
+}
+
+impl<'a> ConstantCombinator<'a> {
+    pub fn into_owned(self) -> ConstantCombinator<'static> {
+        ConstantCombinator{
+            direction: self.direction,
+            circuit_connections: self.circuit_connections,
+            enabled: self.enabled,
+            sections: self.sections.into_iter().map(LogisticSection::into_owned).collect(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for ConstantCombinator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}{}", self.direction, if self.enabled { "" } else { " off" })?;
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        for section in &self.sections {
+            write!(f, "{:?}", section)?;
+        }
+        Ok(())
+    }
+}
+
+// where quality transfer takes the quality
+#[derive(Debug)]
+pub enum QualitySource<'a> {
+    Static(Cow<'a, str>),
+    Signal(Option<Signal<'a>>),
+}
+
+#[derive(Debug)]
+pub enum SelectorMode<'a> {
+    // select the index-th signal sorted by count, index is signal or constant
+    SelectInput{ select_max: bool, index: ConditionOperand<'a> },
+    CountInputs{ output_signal: Option<Signal<'a>> },
+    // interval in ticks
+    RandomInput{ interval: u32 },
+    StackSize,
+    RocketCapacity,
+    QualityFilter{ quality: Option<QualityCondition<'a>> },
+    QualityTransfer{ source: QualitySource<'a>, destination_signal: Option<Signal<'a>> },
+}
+
+impl<'a> SelectorMode<'a> {
+    // the operation name in blueprint json format
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SelectInput{ .. } => "select",
+            Self::CountInputs{ .. } => "count",
+            Self::RandomInput{ .. } => "random",
+            Self::StackSize => "stack-size",
+            Self::RocketCapacity => "rocket-capacity",
+            Self::QualityFilter{ .. } => "quality-filter",
+            Self::QualityTransfer{ .. } => "quality-transfer",
+        }
+    }
+
+    pub fn into_owned(self) -> SelectorMode<'static> {
+        match self {
+            Self::SelectInput{ select_max, index } => SelectorMode::SelectInput{ select_max, index: index.into_owned() },
+            Self::CountInputs{ output_signal } => SelectorMode::CountInputs{ output_signal: output_signal.map(Signal::into_owned) },
+            Self::RandomInput{ interval } => SelectorMode::RandomInput{ interval },
+            Self::StackSize => SelectorMode::StackSize,
+            Self::RocketCapacity => SelectorMode::RocketCapacity,
+            Self::QualityFilter{ quality } => SelectorMode::QualityFilter{ quality: quality.map(QualityCondition::into_owned) },
+            Self::QualityTransfer{ source, destination_signal } => SelectorMode::QualityTransfer{
+                source: match source {
+                    QualitySource::Static(quality) => QualitySource::Static(owned(quality)),
+                    QualitySource::Signal(signal) => QualitySource::Signal(signal.map(Signal::into_owned)),
+                },
+                destination_signal: destination_signal.map(Signal::into_owned),
+            },
+        }
+    }
+}
+
+// NEW in 2.0
+pub struct SelectorCombinator<'a> {
+    pub direction: usize,
+    pub mode: SelectorMode<'a>,
+}
+
+impl<'a> SelectorCombinator<'a> {
+    pub fn into_owned(self) -> SelectorCombinator<'static> {
+        SelectorCombinator{ direction: self.direction, mode: self.mode.into_owned() }
+    }
+}
+
+impl<'a> fmt::Debug for SelectorCombinator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        writeln!(f, "    {:?}", self.mode)
+    }
+}
+
+// ATTENTION NOT SAME 2/SA 1.1 have straight and curved rail, 2.0 have new rail shapes and elevated rails
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RailShape {
+    Straight,
+    // 1.1 only
+    Curved,
+    // NEW in 2.0
+    HalfDiagonal,
+    CurvedA,
+    CurvedB,
+    // between ground and elevated rail, never elevated
+    Ramp,
+    // holds elevated rail, not a rail but built with them, never elevated
+    Support,
+}
+
+pub struct Rail {
+    pub shape: RailShape,
+    // NEW in 2.0
+    pub elevated: bool,
+    pub direction: usize,
+}
+
+impl Rail {
+    // None for not a rail name
+    pub fn from_name(name: &str, direction: usize) -> Option<Self> {
+        let (elevated, shape_name) = match name.strip_prefix("elevated-") {
+            Some(shape_name) => (true, shape_name),
+            None => (false, name),
+        };
+        let shape = match shape_name {
+            "straight-rail" => RailShape::Straight,
+            "curved-rail" if !elevated => RailShape::Curved,
+            "half-diagonal-rail" => RailShape::HalfDiagonal,
+            "curved-rail-a" => RailShape::CurvedA,
+            "curved-rail-b" => RailShape::CurvedB,
+            "rail-ramp" if !elevated => RailShape::Ramp,
+            "rail-support" if !elevated => RailShape::Support,
+            _ => return None,
+        };
+        Some(Self{ shape, elevated, direction })
+    }
+    pub fn name(&self) -> &'static str {
+        match (self.shape, self.elevated) {
+            (RailShape::Straight, false) => "straight-rail",
+            (RailShape::Straight, true) => "elevated-straight-rail",
+            (RailShape::Curved, _) => "curved-rail",
+            (RailShape::HalfDiagonal, false) => "half-diagonal-rail",
+            (RailShape::HalfDiagonal, true) => "elevated-half-diagonal-rail",
+            (RailShape::CurvedA, false) => "curved-rail-a",
+            (RailShape::CurvedA, true) => "elevated-curved-rail-a",
+            (RailShape::CurvedB, false) => "curved-rail-b",
+            (RailShape::CurvedB, true) => "elevated-curved-rail-b",
+            (RailShape::Ramp, _) => "rail-ramp",
+            (RailShape::Support, _) => "rail-support",
+        }
+    }
+}
+
+impl fmt::Debug for Rail {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)
+    }
+}
+
+pub struct RailSignal<'a> {
+    // chain signal or normal rail signal
+    pub chain: bool,
+    pub direction: usize,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    // None for not closed by circuit
+    pub close_condition: Option<CircuitCondition<'a>>,
+    // None for not read signal state, or output signals of red, orange, green, and blue for chain signal only
+    pub read_signals: Option<[Option<Signal<'a>>; 4]>,
+}
+
+impl<'a> RailSignal<'a> {
+    pub fn into_owned(self) -> RailSignal<'static> {
+        RailSignal{
+            chain: self.chain,
+            direction: self.direction,
+            circuit_connections: self.circuit_connections,
+            close_condition: self.close_condition.map(CircuitCondition::into_owned),
+            read_signals: self.read_signals.map(|signals| signals.map(|signal| signal.map(Signal::into_owned))),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for RailSignal<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        if let Some(condition) = &self.close_condition {
+            writeln!(f, "    close if {:?}", condition)?;
+        }
+        if let Some(signals) = &self.read_signals {
+            let colors: &[&str] = if self.chain { &["red", "orange", "green", "blue"] } else { &["red", "orange", "green"] };
+            write!(f, "    read signal")?;
+            for (color, signal) in colors.iter().zip(signals) {
+                write!(f, " {} ", color)?;
+                write_signal(f, signal)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct TrainStop<'a> {
+    pub direction: usize,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    pub station: Cow<'a, str>,
+    // None for no limit
+    pub train_limit: Option<u32>,
+    // NEW in 2.0, default 50
+    pub priority: u8,
+    // control behaviors, None for not enabled
+    pub enable_condition: Option<CircuitCondition<'a>>,
+    pub send_to_train: bool,
+    pub read_from_train: bool,
+    pub read_stopped_train: Option<Option<Signal<'a>>>,
+    pub set_trains_limit: Option<Option<Signal<'a>>>,
+    pub read_trains_count: Option<Option<Signal<'a>>>,
+    // NEW in 2.0
+    pub set_priority: Option<Option<Signal<'a>>>,
+}
+
+impl<'a> TrainStop<'a> {
+    pub fn into_owned(self) -> TrainStop<'static> {
+        let owned_signal = |signal: Option<Option<Signal>>| signal.map(|signal| signal.map(Signal::into_owned));
+        TrainStop{
+            direction: self.direction,
+            circuit_connections: self.circuit_connections,
+            station: owned(self.station),
+            train_limit: self.train_limit,
+            priority: self.priority,
+            enable_condition: self.enable_condition.map(CircuitCondition::into_owned),
+            send_to_train: self.send_to_train,
+            read_from_train: self.read_from_train,
+            read_stopped_train: owned_signal(self.read_stopped_train),
+            set_trains_limit: owned_signal(self.set_trains_limit),
+            read_trains_count: owned_signal(self.read_trains_count),
+            set_priority: owned_signal(self.set_priority),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for TrainStop<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)?;
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        writeln!(f, "    station {} priority {}", self.station, self.priority)?;
+        if let Some(limit) = self.train_limit {
+            writeln!(f, "    train limit {}", limit)?;
+        }
+        if let Some(condition) = &self.enable_condition {
+            writeln!(f, "    enable if {:?}", condition)?;
+        }
+        if self.send_to_train {
+            writeln!(f, "    send to train")?;
+        }
+        if self.read_from_train {
+            writeln!(f, "    read from train")?;
+        }
+        for (name, signal) in [
+            ("read stopped train", &self.read_stopped_train),
+            ("set trains limit from", &self.set_trains_limit),
+            ("read trains count", &self.read_trains_count),
+            ("set priority from", &self.set_priority),
+        ] {
+            if let Some(signal) = signal {
+                write!(f, "    {} ", name)?;
+                write_signal(f, signal)?;
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub enum WaitConditionKind<'a> {
+    Time{ ticks: u32 },
+    Inactivity{ ticks: u32 },
+    Full,
+    Empty,
+    ItemCount(CircuitCondition<'a>),
+    Circuit(CircuitCondition<'a>),
+    RobotsInactive,
+    FluidCount(CircuitCondition<'a>),
+    PassengerPresent,
+    PassengerNotPresent,
+    // NEW in 2.0
+    FuelItemCountAll(CircuitCondition<'a>),
+    FuelItemCountAny(CircuitCondition<'a>),
+    FuelFull,
+    DestinationFullOrNoPath,
+    SpecificDestinationFull(Cow<'a, str>),
+    SpecificDestinationNotFull(Cow<'a, str>),
+    AtStation(Cow<'a, str>),
+    NotAtStation(Cow<'a, str>),
+    DamageTaken{ damage: u32 },
+}
+
+impl<'a> WaitConditionKind<'a> {
+    // the type name in blueprint json format
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Time{ .. } => "time",
+            Self::Inactivity{ .. } => "inactivity",
+            Self::Full => "full",
+            Self::Empty => "empty",
+            Self::ItemCount(_) => "item_count",
+            Self::Circuit(_) => "circuit",
+            Self::RobotsInactive => "robots_inactive",
+            Self::FluidCount(_) => "fluid_count",
+            Self::PassengerPresent => "passenger_present",
+            Self::PassengerNotPresent => "passenger_not_present",
+            Self::FuelItemCountAll(_) => "fuel_item_count_all",
+            Self::FuelItemCountAny(_) => "fuel_item_count_any",
+            Self::FuelFull => "fuel_full",
+            Self::DestinationFullOrNoPath => "destination_full_or_no_path",
+            Self::SpecificDestinationFull(_) => "specific_destination_full",
+            Self::SpecificDestinationNotFull(_) => "specific_destination_not_full",
+            Self::AtStation(_) => "at_station",
+            Self::NotAtStation(_) => "not_at_station",
+            Self::DamageTaken{ .. } => "damage_taken",
+        }
+    }
+
+    pub fn into_owned(self) -> WaitConditionKind<'static> {
+        match self {
+            Self::Time{ ticks } => WaitConditionKind::Time{ ticks },
+            Self::Inactivity{ ticks } => WaitConditionKind::Inactivity{ ticks },
+            Self::Full => WaitConditionKind::Full,
+            Self::Empty => WaitConditionKind::Empty,
+            Self::ItemCount(condition) => WaitConditionKind::ItemCount(condition.into_owned()),
+            Self::Circuit(condition) => WaitConditionKind::Circuit(condition.into_owned()),
+            Self::RobotsInactive => WaitConditionKind::RobotsInactive,
+            Self::FluidCount(condition) => WaitConditionKind::FluidCount(condition.into_owned()),
+            Self::PassengerPresent => WaitConditionKind::PassengerPresent,
+            Self::PassengerNotPresent => WaitConditionKind::PassengerNotPresent,
+            Self::FuelItemCountAll(condition) => WaitConditionKind::FuelItemCountAll(condition.into_owned()),
+            Self::FuelItemCountAny(condition) => WaitConditionKind::FuelItemCountAny(condition.into_owned()),
+            Self::FuelFull => WaitConditionKind::FuelFull,
+            Self::DestinationFullOrNoPath => WaitConditionKind::DestinationFullOrNoPath,
+            Self::SpecificDestinationFull(station) => WaitConditionKind::SpecificDestinationFull(owned(station)),
+            Self::SpecificDestinationNotFull(station) => WaitConditionKind::SpecificDestinationNotFull(owned(station)),
+            Self::AtStation(station) => WaitConditionKind::AtStation(owned(station)),
+            Self::NotAtStation(station) => WaitConditionKind::NotAtStation(owned(station)),
+            Self::DamageTaken{ damage } => WaitConditionKind::DamageTaken{ damage },
+        }
+    }
+}
+
+// like "time 300", "item_count Item iron-plate > 100", "at_station Iron Drop"
+impl<'a> fmt::Debug for WaitConditionKind<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        match self {
+            Self::Time{ ticks } | Self::Inactivity{ ticks } => write!(f, " {}", ticks),
+            Self::DamageTaken{ damage } => write!(f, " {}", damage),
+            Self::ItemCount(condition) | Self::Circuit(condition) | Self::FluidCount(condition)
+            | Self::FuelItemCountAll(condition) | Self::FuelItemCountAny(condition) => write!(f, " {:?}", condition),
+            Self::SpecificDestinationFull(station) | Self::SpecificDestinationNotFull(station)
+            | Self::AtStation(station) | Self::NotAtStation(station) => write!(f, " {}", station),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WaitCondition<'a> {
+    // ignored for first condition, see DeciderCondition
+    pub join: ConditionJoin,
+    pub kind: WaitConditionKind<'a>,
+}
+
+#[derive(Clone)]
+pub struct ScheduleRecord<'a> {
+    pub station: Cow<'a, str>,
+    pub temporary: bool,
+    pub wait_conditions: Vec<WaitCondition<'a>>,
+}
+
+// NEW in 2.0, go to targets when conditions are met
+#[derive(Clone)]
+pub struct ScheduleInterrupt<'a> {
+    pub name: Cow<'a, str>,
+    // can interrupt another interrupt
+    pub inside_interrupt: bool,
+    pub conditions: Vec<WaitCondition<'a>>,
+    pub targets: Vec<ScheduleRecord<'a>>,
+}
+
+#[derive(Clone)]
+pub struct Schedule<'a> {
+    pub records: Vec<ScheduleRecord<'a>>,
+    // NEW in 2.0
+    pub interrupts: Vec<ScheduleInterrupt<'a>>,
+}
+
+fn owned_wait_conditions(conditions: Vec<WaitCondition>) -> Vec<WaitCondition<'static>> {
+    conditions.into_iter().map(|condition| WaitCondition{ join: condition.join, kind: condition.kind.into_owned() }).collect()
+}
+fn owned_schedule_records(records: Vec<ScheduleRecord>) -> Vec<ScheduleRecord<'static>> {
+    records.into_iter().map(|record| ScheduleRecord{
+        station: owned(record.station),
+        temporary: record.temporary,
+        wait_conditions: owned_wait_conditions(record.wait_conditions),
+    }).collect()
+}
+
+impl<'a> Schedule<'a> {
+    pub fn into_owned(self) -> Schedule<'static> {
+        Schedule{
+            records: owned_schedule_records(self.records),
+            interrupts: self.interrupts.into_iter().map(|interrupt| ScheduleInterrupt{
+                name: owned(interrupt.name),
+                inside_interrupt: interrupt.inside_interrupt,
+                conditions: owned_wait_conditions(interrupt.conditions),
+                targets: owned_schedule_records(interrupt.targets),
+            }).collect(),
+        }
+    }
+}
+
+fn write_wait_conditions(f: &mut fmt::Formatter, conditions: &[WaitCondition], indent: &str) -> fmt::Result {
+    for (index, condition) in conditions.iter().enumerate() {
+        let join = match (index, condition.join) {
+            (0, _) => "wait",
+            (_, ConditionJoin::Or) => "or",
+            (_, ConditionJoin::And) => "and",
+        };
+        writeln!(f, "{}{} {:?}", indent, join, condition.kind)?;
+    }
+    Ok(())
+}
+fn write_schedule_records(f: &mut fmt::Formatter, records: &[ScheduleRecord], indent: &str) -> fmt::Result {
+    for record in records {
+        writeln!(f, "{}station {}{}", indent, record.station, if record.temporary { " (temporary)" } else { "" })?;
+        write_wait_conditions(f, &record.wait_conditions, &format!("{}  ", indent))?;
+    }
+    Ok(())
+}
+
+impl<'a> fmt::Debug for Schedule<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_schedule_records(f, &self.records, "      ")?;
+        for interrupt in &self.interrupts {
+            writeln!(f, "      interrupt {}{}", interrupt.name, if interrupt.inside_interrupt { " (inside interrupt)" } else { "" })?;
+            write_wait_conditions(f, &interrupt.conditions, "        ")?;
+            write_schedule_records(f, &interrupt.targets, "        ")?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Locomotive<'a> {
+    // 0 for north, 0.25 for east, and so on
+    pub orientation: f32,
+    pub schedule: Option<Schedule<'a>>,
+}
+
+impl<'a> Locomotive<'a> {
+    pub fn into_owned(self) -> Locomotive<'static> {
+        Locomotive{ orientation: self.orientation, schedule: self.schedule.map(Schedule::into_owned) }
+    }
+}
+
+impl<'a> fmt::Debug for Locomotive<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    orientation {}", self.orientation)?;
+        if let Some(schedule) = &self.schedule {
+            writeln!(f, "    schedule")?;
+            write!(f, "{:?}", schedule)?;
+        }
+        Ok(())
+    }
+}
+
+// logistic chests are named logistic-chest-* in 1.1, e.g. logistic-chest-requester
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContainerType {
+    Wooden,
+    Iron,
+    Steel,
+    ActiveProvider,
+    PassiveProvider,
+    Storage,
+    Buffer,
+    Requester,
+}
+
+impl ContainerType {
+    // with whether it is the 1.1 name
+    pub fn from_name(name: &str) -> Option<(Self, bool)> {
+        Some(match name {
+            "wooden-chest" => (Self::Wooden, false),
+            "iron-chest" => (Self::Iron, false),
+            "steel-chest" => (Self::Steel, false),
+            "active-provider-chest" => (Self::ActiveProvider, false),
+            "passive-provider-chest" => (Self::PassiveProvider, false),
+            "storage-chest" => (Self::Storage, false),
+            "buffer-chest" => (Self::Buffer, false),
+            "requester-chest" => (Self::Requester, false),
+            "logistic-chest-active-provider" => (Self::ActiveProvider, true),
+            "logistic-chest-passive-provider" => (Self::PassiveProvider, true),
+            "logistic-chest-storage" => (Self::Storage, true),
+            "logistic-chest-buffer" => (Self::Buffer, true),
+            "logistic-chest-requester" => (Self::Requester, true),
+            _ => return None,
+        })
+    }
+    pub fn name(self, legacy_name: bool) -> &'static str {
+        match (self, legacy_name) {
+            (Self::Wooden, _) => "wooden-chest",
+            (Self::Iron, _) => "iron-chest",
+            (Self::Steel, _) => "steel-chest",
+            (Self::ActiveProvider, false) => "active-provider-chest",
+            (Self::PassiveProvider, false) => "passive-provider-chest",
+            (Self::Storage, false) => "storage-chest",
+            (Self::Buffer, false) => "buffer-chest",
+            (Self::Requester, false) => "requester-chest",
+            (Self::ActiveProvider, true) => "logistic-chest-active-provider",
+            (Self::PassiveProvider, true) => "logistic-chest-passive-provider",
+            (Self::Storage, true) => "logistic-chest-storage",
+            (Self::Buffer, true) => "logistic-chest-buffer",
+            (Self::Requester, true) => "logistic-chest-requester",
+        }
+    }
+    pub fn has_request(self) -> bool {
+        matches!(self, Self::Buffer | Self::Requester)
+    }
+}
+
+// requests of requester and buffer chests and space platform hub,
+// 1.1 have no section, its requests are one active section without group
+pub struct LogisticRequest<'a> {
+    pub sections: Vec<LogisticSection<'a>>,
+    // NEW in 2.0
+    pub trash_unrequested: bool,
+    // requester chest only
+    pub request_from_buffers: bool,
+}
+
+impl<'a> LogisticRequest<'a> {
+    pub fn into_owned(self) -> LogisticRequest<'static> {
+        LogisticRequest{
+            sections: self.sections.into_iter().map(LogisticSection::into_owned).collect(),
+            trash_unrequested: self.trash_unrequested,
+            request_from_buffers: self.request_from_buffers,
+        }
+    }
+}
+
+impl<'a> fmt::Debug for LogisticRequest<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.trash_unrequested {
+            writeln!(f, "    trash unrequested")?;
+        }
+        if self.request_from_buffers {
+            writeln!(f, "    request from buffers")?;
+        }
+        for section in &self.sections {
+            write!(f, "{:?}", section)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Container<'a> {
+    pub container: ContainerType,
+    pub legacy_name: bool,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    // number of usable slots, None for not limited
+    pub bar: Option<u16>,
+    // storage chest only
+    pub filter: Option<EntityFilter<'a>>,
+    // requester and buffer chests only
+    pub request: Option<LogisticRequest<'a>>,
+}
+
+impl<'a> Container<'a> {
+    pub fn into_owned(self) -> Container<'static> {
+        Container{
+            container: self.container,
+            legacy_name: self.legacy_name,
+            circuit_connections: self.circuit_connections,
+            bar: self.bar,
+            filter: self.filter.map(EntityFilter::into_owned),
+            request: self.request.map(LogisticRequest::into_owned),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for Container<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        if let Some(bar) = self.bar {
+            writeln!(f, "    bar {}", bar)?;
+        }
+        if let Some(filter) = &self.filter {
+            writeln!(f, "    filter {:?}", filter)?;
+        }
+        if let Some(request) = &self.request {
+            write!(f, "{:?}", request)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct CargoWagon<'a> {
+    // see Locomotive
+    pub orientation: f32,
+    pub bar: Option<u16>,
+    // (slot index, filter)[], empty slots are not included
+    pub filters: Vec<(usize, EntityFilter<'a>)>,
+}
+
+impl<'a> CargoWagon<'a> {
+    pub fn into_owned(self) -> CargoWagon<'static> {
+        CargoWagon{
+            orientation: self.orientation,
+            bar: self.bar,
+            filters: self.filters.into_iter().map(|(index, filter)| (index, filter.into_owned())).collect(),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for CargoWagon<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    orientation {}", self.orientation)?;
+        if let Some(bar) = self.bar {
+            writeln!(f, "    bar {}", bar)?;
+        }
+        for (index, filter) in &self.filters {
+            writeln!(f, "    filter #{} {:?}", index, filter)?;
+        }
+        Ok(())
+    }
+}
+
+// NEW in space age
+pub struct SpacePlatformHub<'a> {
+    pub request: LogisticRequest<'a>,
+}
+
+impl<'a> SpacePlatformHub<'a> {
+    pub fn into_owned(self) -> SpacePlatformHub<'static> {
+        SpacePlatformHub{ request: self.request.into_owned() }
+    }
+}
+
+impl<'a> fmt::Debug for SpacePlatformHub<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.request)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PoleType {
+    Small,
+    Medium,
+    Big,
+    Substation,
+}
+
+impl PoleType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "small-electric-pole" => Self::Small,
+            "medium-electric-pole" => Self::Medium,
+            "big-electric-pole" => Self::Big,
+            "substation" => Self::Substation,
+            _ => return None,
+        })
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Small => "small-electric-pole",
+            Self::Medium => "medium-electric-pole",
+            Self::Big => "big-electric-pole",
+            Self::Substation => "substation",
+        }
+    }
+}
+
+pub struct ElectricPole {
+    pub pole: PoleType,
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    // ATTENTION NOT SAME 2/SA only 1.1 have copper connections in entity, entity id[], 2.0 use blueprint wires
+    pub copper_connections: Option<Vec<usize>>,
+}
+
+impl fmt::Debug for ElectricPole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        if let Some(neighbours) = &self.copper_connections {
+            writeln!(f, "    copper connections {:?}", neighbours)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct PowerSwitch<'a> {
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    // ATTENTION NOT SAME 2/SA only 1.1 have copper connections in entity, entity id[] of left and right side
+    pub copper_connections: Option<[Vec<usize>; 2]>,
+    // on for connected, that is the state when not controlled by circuit
+    pub switch_state: bool,
+    pub circuit_condition: Option<CircuitCondition<'a>>,
+    pub logistic_condition: Option<CircuitCondition<'a>>,
+}
+
+impl<'a> PowerSwitch<'a> {
+    pub fn into_owned(self) -> PowerSwitch<'static> {
+        PowerSwitch{
+            circuit_connections: self.circuit_connections,
+            copper_connections: self.copper_connections,
+            switch_state: self.switch_state,
+            circuit_condition: self.circuit_condition.map(CircuitCondition::into_owned),
+            logistic_condition: self.logistic_condition.map(CircuitCondition::into_owned),
+        }
+    }
+}
+
+impl<'a> fmt::Debug for PowerSwitch<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        if let Some([left, right]) = &self.copper_connections {
+            writeln!(f, "    copper connections left {:?} right {:?}", left, right)?;
+        }
+        writeln!(f, "    switch {}", if self.switch_state { "on" } else { "off" })?;
+        if let Some(condition) = &self.circuit_condition {
+            writeln!(f, "    enable if {:?}", condition)?;
+        }
+        if let Some(condition) = &self.logistic_condition {
+            writeln!(f, "    logistic enable if {:?}", condition)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Accumulator<'a> {
+    // ATTENTION NOT SAME 2/SA only 1.1 have circuit connections in entity
+    pub circuit_connections: Option<CircuitConnections>,
+    // charge percentage output, None for not set
+    pub output_signal: Option<Signal<'a>>,
+}
+
+impl<'a> Accumulator<'a> {
+    pub fn into_owned(self) -> Accumulator<'static> {
+        Accumulator{ circuit_connections: self.circuit_connections, output_signal: self.output_signal.map(Signal::into_owned) }
+    }
+}
+
+impl<'a> fmt::Debug for Accumulator<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(connections) = &self.circuit_connections {
+            writeln!(f, "    circuit connections red {:?} green {:?}", connections.red, connections.green)?;
+        }
+        if let Some(signal) = &self.output_signal {
+            writeln!(f, "    output signal {:?}", signal)?;
+        }
+        Ok(())
+    }
+}
+
+// generators and the entities feeding them, only have direction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PowerProducerType {
+    Boiler,
+    SteamEngine,
+    SteamTurbine,
+    SolarPanel,
+    NuclearReactor,
+    HeatExchanger,
+    // NEW in space age
+    HeatingTower,
+    FusionReactor,
+    FusionGenerator,
+}
+
+impl PowerProducerType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "boiler" => Self::Boiler,
+            "steam-engine" => Self::SteamEngine,
+            "steam-turbine" => Self::SteamTurbine,
+            "solar-panel" => Self::SolarPanel,
+            "nuclear-reactor" => Self::NuclearReactor,
+            "heat-exchanger" => Self::HeatExchanger,
+            "heating-tower" => Self::HeatingTower,
+            "fusion-reactor" => Self::FusionReactor,
+            "fusion-generator" => Self::FusionGenerator,
+            _ => return None,
+        })
+    }
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Boiler => "boiler",
+            Self::SteamEngine => "steam-engine",
+            Self::SteamTurbine => "steam-turbine",
+            Self::SolarPanel => "solar-panel",
+            Self::NuclearReactor => "nuclear-reactor",
+            Self::HeatExchanger => "heat-exchanger",
+            Self::HeatingTower => "heating-tower",
+            Self::FusionReactor => "fusion-reactor",
+            Self::FusionGenerator => "fusion-generator",
+        }
+    }
+}
+
+pub struct PowerProducer {
+    pub producer: PowerProducerType,
+    pub direction: usize,
+}
+
+impl fmt::Debug for PowerProducer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    direction {}", self.direction)
+    }
+}
+
+// entity not handled yet, only from json, other json fields are kept as is to write back,
+// binary layout is unknown so it is not written to library file
+pub struct UnknownEntity<'a> {
+    pub name: Cow<'a, str>,
+    pub fields: Map<String, Value>,
+}
+
+impl<'a> UnknownEntity<'a> {
+    pub fn into_owned(self) -> UnknownEntity<'static> {
+        UnknownEntity{ name: owned(self.name), fields: self.fields }
+    }
+}
+
+impl<'a> fmt::Debug for UnknownEntity<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "    unknown {}", Value::from(self.fields.clone()))
+    }
+}
@@ -0,0 +1,85 @@
+// parse error with where it happens,
+// byte offset and the logical path like prints[3].entities[12].position.x, see trace.rs,
+// so that tools can decide what to do by kind, e.g. skip unknown entity
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::Utf8Error;
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub offset: usize,
+    pub path: String,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    // expected is a byte value like 0x20, or the meaning of the byte like "print type"
+    UnexpectedByte{ expected: Cow<'static, str>, actual: u8 },
+    // reading to end but buffer only have length
+    OutOfRange{ end: usize, length: usize },
+    InvalidUtf8(Utf8Error),
+    UnknownEntity(String),
+    // namespace is like "item", "entity", see name.rs
+    UnknownNameIndex{ namespace: &'static str, index: usize },
+    // understood but not supported for now
+    Unsupported(&'static str),
+    // other invalid data like name mismatch
+    Invalid(String),
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnexpectedByte{ expected, actual } => write!(f, "expect {expected}, actual 0x{actual:x}"),
+            Self::OutOfRange{ end, length } => write!(f, "read to 0x{end:x} out of range 0x{length:x}"),
+            Self::InvalidUtf8(error) => write!(f, "invalid utf-8: {error}"),
+            Self::UnknownEntity(name) => write!(f, "unknown entity {name}"),
+            Self::UnknownNameIndex{ namespace, index } => write!(f, "unknown {namespace} name index {index}"),
+            Self::Unsupported(what) => write!(f, "not support {what} for now"),
+            Self::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+impl std::error::Error for ParseErrorKind {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:x} {}: {}", self.offset, self.path, self.kind)
+    }
+}
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::InvalidUtf8(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_reader::Reader;
+    use super::*;
+
+    #[test]
+    fn short_buffer() {
+        let mut reader = Reader::new(b"\x01\x00");
+        reader.enter_index("prints", 0);
+        reader.field("active").read_bool().unwrap();
+        let error = reader.field("generation").read_u32().unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::OutOfRange{ end: 5, length: 2 }));
+        assert_eq!((error.offset, error.path.as_str()), (1, "prints[0].generation"));
+
+        let error = reader.expect(0x20).unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::UnexpectedByte{ actual: 0, .. }));
+        assert_eq!(error.to_string(), "0x1 prints[0]: expect 0x20, actual 0x0");
+
+        let mut reader = Reader::new(b"");
+        assert!(matches!(reader.read_u8().unwrap_err().kind, ParseErrorKind::OutOfRange{ end: 1, length: 0 }));
+        assert!(matches!(reader.expect(0).unwrap_err().kind, ParseErrorKind::OutOfRange{ .. }));
+    }
+}
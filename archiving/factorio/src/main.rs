@@ -2,26 +2,240 @@
 // inspired by/learn from https://github.com/asheiduk/factorio-blueprint-decoder/blob/master/decode
 // but that's old and not for 2.0 and space age
 
-use anyhow::Context;
+use anyhow::{anyhow, bail, Context};
 use std::fs::File;
 use std::io::Read;
 
+use blueprint_library::{BlueprintLibrary, Print};
+
+mod bill;
 mod binary_reader;
+mod binary_writer;
 mod blueprint_library;
+mod blueprint_string;
+mod entity;
+mod error;
 mod name;
 mod parser;
+mod render;
+mod serializer;
+mod trace;
+
+const USAGE: &str = "usage: factorio-blueprint-utilities [--file <path>] [--trace | --trace-json] [command]
+file is blueprint-storage.dat by default, or exchange string if ends with .txt, or its json if ends with .json
+library file only decodes roboport and underground belt entities, blueprint with other entities, tags, tiles, 2.0 wires,
+icons or parameters is listed as skipped and written back as is, exchange string decodes all of them,
+library file with book or planner that has icons or quality, or with blueprint that has removed mods, cannot be read
+commands:
+  list                           library tree with print kinds and labels, the default
+  show <path>                    one print in detail
+  extract <path> [-o <output>]   one print as exchange string, or json if output ends with .json, stdout if no output
+  export [path] [-o <output>]    whole library or one book as exchange string of a book, or json if output ends with .json,
+                                 whole library is written back as library file if output ends with .dat, stdout if no output
+  bill [path] [--json]           items needed to build one print or the whole library, by quality,
+                                 quality is only decoded from exchange string, library file counts all as normal
+  render <path> [-o <output>]    one blueprint as svg preview, stdout if no output
+path is labels separated by /, like \"mall/iron gear\", use #<slot index> for print without label or with same label";
+
+enum Command {
+    List,
+    Show(String),
+    Extract(String, Option<String>),
+    // None for whole library
+    Export(Option<String>, Option<String>),
+    // None for whole library
+    Bill(Option<String>),
+    Render(String, Option<String>),
+}
 
 fn main() -> anyhow::Result<()> {
 
+    let mut file_name = "blueprint-storage.dat".to_string();
+    let mut trace = None;
+    let mut json = false;
+    let mut arguments = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--file" => file_name = args.next().ok_or_else(|| anyhow!("--file requires a path\n{USAGE}"))?,
+            "--trace" | "--trace-json" => trace = Some(arg),
+            "--json" => json = true,
+            "-o" => {
+                let output = args.next().ok_or_else(|| anyhow!("-o requires a path\n{USAGE}"))?;
+                arguments.push(arg);
+                arguments.push(output);
+            },
+            "--help" | "-h" => {
+                println!("{USAGE}");
+                return Ok(());
+            },
+            other if other.starts_with("--") => bail!("unknown option {other}\n{USAGE}"),
+            _ => arguments.push(arg),
+        }
+    }
+    let command = match arguments.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        [] | ["list"] => Command::List,
+        ["show", path] => Command::Show(path.to_string()),
+        ["extract", path] => Command::Extract(path.to_string(), None),
+        ["extract", path, "-o", output] => Command::Extract(path.to_string(), Some(output.to_string())),
+        ["export"] => Command::Export(None, None),
+        ["export", "-o", output] => Command::Export(None, Some(output.to_string())),
+        ["export", path] => Command::Export(Some(path.to_string()), None),
+        ["export", path, "-o", output] => Command::Export(Some(path.to_string()), Some(output.to_string())),
+        ["bill"] => Command::Bill(None),
+        ["bill", path] => Command::Bill(Some(path.to_string())),
+        ["render", path] => Command::Render(path.to_string(), None),
+        ["render", path, "-o", output] => Command::Render(path.to_string(), Some(output.to_string())),
+        _ => bail!("invalid command {arguments:?}\n{USAGE}"),
+    };
+    if json && !matches!(command, Command::Bill(_)) { bail!("--json is only for bill\n{USAGE}"); }
+
     let mut buffer = Vec::new();
-    let mut file = File::open("blueprint-storage.dat").with_context(|| "failed to open buluprint-storage.dat")?;
-    file.read_to_end(&mut buffer).with_context(|| "failed to read blueprint-storage.dat")?;
-    println!("file size {}", buffer.len());
+    let mut file = File::open(&file_name).with_context(|| format!("failed to open {file_name}"))?;
+    file.read_to_end(&mut buffer).with_context(|| format!("failed to read {file_name}"))?;
 
-    let mut parser = parser::Parser::new(binary_reader::Reader::new(&buffer));
-    let library = parser.parse()?;
+    // the only print in exchange string is put in a library, the print borrows the json
+    let string_json;
+    let from_string = file_name.ends_with(".txt") || file_name.ends_with(".json");
+    let library = if from_string {
+        if trace.is_some() { bail!("trace is only for library file"); }
+        let text = std::str::from_utf8(&buffer).with_context(|| format!("invalid text in {file_name}"))?;
+        string_json = if file_name.ends_with(".json") { serde_json::from_str(text)? } else { blueprint_string::decode(text)? };
+        blueprint_string::library_from_json(&string_json)?
+    } else {
+        // --trace for annotated hex dump, --trace-json for json, print before result so available when parse fail
+        let reader = match trace {
+            Some(_) => binary_reader::Reader::with_trace(&buffer),
+            None => binary_reader::Reader::new(&buffer),
+        };
+        let mut parser = parser::Parser::new(reader);
+        let library = parser.parse();
+        match trace.as_deref() {
+            Some("--trace") => print!("{}", trace::render_hex_dump(&parser.take_trace())),
+            Some("--trace-json") => println!("{}", serde_json::to_string_pretty(&trace::render_json(&parser.take_trace()))?),
+            _ => {},
+        }
+        library?
+    };
 
-    println!("{library:?}");
+    match command {
+        Command::List => {
+            print_list(&library.prints, 0);
+            for skipped in &library.skipped {
+                println!("skipped {:?} {}: {}", skipped.slot_path, skipped.label, skipped.reason);
+            }
+        },
+        Command::Show(path) => {
+            let (slot_index, print) = find_print(&library, &path)?;
+            print!("#{slot_index} {print:?}");
+            if let Print::Blueprint(blueprint) = print {
+                for (index, poles) in blueprint.electric_networks().iter().enumerate() {
+                    println!("  electric network {index} poles {poles:?}");
+                }
+            }
+        },
+        Command::Extract(path, output) => {
+            let (_, print) = find_print(&library, &path)?;
+            match output {
+                Some(output) if output.ends_with(".json") => {
+                    let json = blueprint_string::print_to_json(print, library.file_version);
+                    std::fs::write(&output, serde_json::to_string_pretty(&json)?).with_context(|| format!("failed to write {output}"))?;
+                },
+                Some(output) => {
+                    let text = blueprint_string::encode(print, library.file_version)?;
+                    std::fs::write(&output, text).with_context(|| format!("failed to write {output}"))?;
+                },
+                None => println!("{}", blueprint_string::encode(print, library.file_version)?),
+            }
+        },
+        Command::Export(path, output) => {
+            if let Some(output) = output.as_ref().filter(|output| output.ends_with(".dat")) {
+                if path.is_some() { bail!("only whole library can be written as library file"); }
+                // names of library file are not in exchange string
+                if from_string { bail!("library file can only be written from library file"); }
+                let mut serializer = serializer::Serializer::new(binary_writer::Writer::new());
+                serializer.serialize(&library)?;
+                std::fs::write(output, serializer.into_inner()).with_context(|| format!("failed to write {output}"))?;
+                return Ok(());
+            }
+            let json = match &path {
+                Some(path) => match find_print(&library, path)?.1 {
+                    print @ Print::BlueprintBook(_) => blueprint_string::print_to_json(print, library.file_version),
+                    _ => bail!("{path:?} is not a blueprint book, use extract for other prints"),
+                },
+                None => blueprint_string::library_to_json(&library),
+            };
+            match output {
+                Some(output) if output.ends_with(".json") => {
+                    std::fs::write(&output, serde_json::to_string_pretty(&json)?).with_context(|| format!("failed to write {output}"))?;
+                },
+                Some(output) => {
+                    let text = blueprint_string::encode_json(&json)?;
+                    std::fs::write(&output, text).with_context(|| format!("failed to write {output}"))?;
+                },
+                None => println!("{}", blueprint_string::encode_json(&json)?),
+            }
+        },
+        Command::Bill(path) => {
+            let mut bill = bill::BillOfMaterials::default();
+            match path {
+                Some(path) => bill.add_print(find_print(&library, &path)?.1),
+                None => for (_, print) in &library.prints {
+                    bill.add_print(print);
+                },
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&bill::render_json(&bill))?);
+            } else {
+                print!("{}", bill::render_table(&bill));
+            }
+        },
+        Command::Render(path, output) => {
+            let Print::Blueprint(blueprint) = find_print(&library, &path)?.1 else {
+                bail!("{path:?} is not a blueprint");
+            };
+            let svg = render::render_svg(blueprint);
+            match output {
+                Some(output) => std::fs::write(&output, svg).with_context(|| format!("failed to write {output}"))?,
+                None => print!("{svg}"),
+            }
+        },
+    }
 
     Ok(())
 }
+
+// one line per print with slot index, kind and label, book content indented
+fn print_list(prints: &[(usize, Print)], depth: usize) {
+    for (slot_index, print) in prints {
+        let label = if print.label().is_empty() { "(no label)" } else { print.label() };
+        println!("{:indent$}#{slot_index} {}: {label}", "", print.kind_name(), indent = depth * 2);
+        if let Print::BlueprintBook(book) = print {
+            print_list(&book.prints, depth + 1);
+        }
+    }
+}
+
+// each path segment is a label or #<slot index>, all segments except last are books
+fn find_print<'l, 'a>(library: &'l BlueprintLibrary<'a>, path: &str) -> anyhow::Result<(usize, &'l Print<'a>)> {
+    let mut prints = library.prints.as_slice();
+    let mut segments = path.split('/').peekable();
+    while let Some(segment) = segments.next() {
+        let mut candidates = prints.iter().filter(|(slot_index, print)| match segment.strip_prefix('#') {
+            Some(index) if index.parse() == Ok(*slot_index) => true,
+            _ => print.label() == segment,
+        });
+        let (slot_index, print) = candidates.next().ok_or_else(|| anyhow!("{segment:?} not found in {path:?}"))?;
+        if candidates.next().is_some() {
+            bail!("more than one print labeled {segment:?} in {path:?}, use #<slot index> from list");
+        }
+        if segments.peek().is_none() {
+            return Ok((*slot_index, print));
+        }
+        match print {
+            Print::BlueprintBook(book) => prints = &book.prints,
+            _ => bail!("{segment:?} in {path:?} is not a blueprint book"),
+        }
+    }
+    bail!("empty path")
+}
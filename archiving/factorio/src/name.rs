@@ -4,33 +4,63 @@
 // TODO when talking about blueprint library file format, is it proper to call this name collection "names"?
 // but for now, the different bucket for indexes are called namespace
 
-use anyhow::bail;
+use anyhow::anyhow;
+use std::borrow::Cow;
 use std::fmt;
 
-// the strings are still borrowing the buffer
+use crate::error::ParseErrorKind;
+
+// the strings are borrowing the buffer unless into_owned
 pub struct Names<'a> {
     // ATTENTION NOT SAME 2/SA obiviously have new namespace quality and planet
     // 8 namespaces, see later
     // there are less than 300 items in each namespace,
     // use sparse array seems good, not used entries use static empty string
-    names: [Vec<&'a str>; 8],
+    names: [Vec<Cow<'a, str>>; 8],
+    // in file order, used to write back
+    prototypes: Vec<Prototype<'a>>,
 }
 
-#[allow(dead_code)]
+// (prototype name, (index, name)[])
+pub type Prototype<'a> = (Cow<'a, str>, Vec<(usize, Cow<'a, str>)>);
+
 impl<'a> Names<'a> {
 
     pub fn new() -> Self {
-        Self{ names: Default::default() }
+        Self{ names: Default::default(), prototypes: Vec::new() }
+    }
+
+    // names added after this belongs to this prototype
+    pub fn add_prototype(&mut self, prototype_name: impl Into<Cow<'a, str>>) {
+        self.prototypes.push((prototype_name.into(), Vec::new()));
+    }
+    pub fn prototypes(&self) -> &[Prototype<'a>] {
+        &self.prototypes
+    }
+
+    pub fn into_owned(self) -> Names<'static> {
+        let owned = |name: Cow<str>| Cow::<'static, str>::Owned(name.into_owned());
+        Names{
+            names: self.names.map(|names| names.into_iter().map(owned).collect()),
+            prototypes: self.prototypes.into_iter().map(|(prototype_name, entries)|
+                (owned(prototype_name), entries.into_iter().map(|(index, name)| (index, owned(name))).collect())).collect(),
+        }
     }
 
     // prototype name is used to determine namespace // TODO is this correct?
-    // for now prototype name is not stored
-    pub fn add(&mut self, index: usize, name: &'a str, prototype_name: &'a str) -> anyhow::Result<()> {
+    #[allow(clippy::comparison_to_empty)]
+    pub fn add(&mut self, index: usize, name: impl Into<Cow<'a, str>>, prototype_name: &str) -> Result<(), ParseErrorKind> {
+        let name = name.into();
+        match self.prototypes.last_mut() {
+            Some((last_prototype_name, entries)) if *last_prototype_name == prototype_name => entries.push((index, name.clone())),
+            _ => return Err(ParseErrorKind::Invalid(format!("name {name} added before its prototype {prototype_name}"))),
+        }
         let namespace_index = match prototype_name {
             | "capsule" // (capsule, raw fish) is item
             | "gun" // (gun, rocket launcher) is item? // NOTE rocket luancher is the gun, not rocket silo
             | "module" // speed module, productivity module, etc.
             | "blueprint"
+            | "blueprint-book"
             | "upgrade-item"
             | "deconstruction-item"
             | "rail-planner" // what is rail planner
@@ -48,56 +78,79 @@ impl<'a> Names<'a> {
             _ => 2,
         };
         if self.names[namespace_index].len() < index + 1 {
-            self.names[namespace_index].resize(index + 1, "");
+            self.names[namespace_index].resize(index + 1, Cow::Borrowed(""));
         }
         if self.names[namespace_index][index] != "" {
-            bail!("namespace {} duplicate index {} name {} prototype name {}", namespace_index, index, name, prototype_name);
+            return Err(ParseErrorKind::Invalid(format!("namespace {namespace_index} duplicate index {index} name {name} prototype name {prototype_name}")));
         }
         self.names[namespace_index][index] = name;
         Ok(())
     }
 
+    // None for out of range or not used entry
+    fn get_name(&self, namespace_index: usize, index: usize) -> Option<Cow<'a, str>> {
+        self.names[namespace_index].get(index).filter(|name| !name.is_empty()).cloned()
+    }
+
     // NOTE self.names stored in this order
 
-    pub fn get_item_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[0].len() <= index { bail!("name index out of range"); }
-        if self.names[0][index] == "" { bail!("item name index {} invalid\n{:?}", index, self.names[0]); }
-        Ok(self.names[0][index])
-    }
-    pub fn get_recipe_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[1].len() <= index { bail!("name index out of range"); }
-        if self.names[1][index] == "" { bail!("recipe name index {} invalid\n{:?}", index, self.names[1]); }
-        Ok(self.names[1][index])
-    }
-    pub fn get_entity_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[2].len() <= index { bail!("name index out of range"); }
-        if self.names[2][index] == "" { bail!("entity name index {} invalid\n{:?}", index, self.names[2]); }
-        Ok(self.names[2][index])
-    }
-    pub fn get_tile_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[3].len() <= index { bail!("name index out of range"); }
-        if self.names[3][index] == "" { bail!("tile name index {} invalid\n{:?}", index, self.names[3]); }
-        Ok(self.names[3][index])
-    }
-    pub fn get_virtual_signal_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[4].len() <= index { bail!("name index out of range"); }
-        if self.names[4][index] == "" { bail!("vsignal name index {} invalid\n{:?}", index, self.names[4]); }
-        Ok(self.names[4][index])
-    }
-    pub fn get_fluid_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[5].len() <= index { bail!("name index out of range"); }
-        if self.names[5][index] == "" { bail!("fluid name index {} invalid\n{:?}", index, self.names[5]); }
-        Ok(self.names[5][index])
-    }
-    pub fn get_quality_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[6].len() <= index { bail!("name index out of range"); }
-        if self.names[6][index] == "" { bail!("quality name index {} invalid\n{:?}", index, self.names[6]); }
-        Ok(self.names[6][index])
-    }
-    pub fn get_planet_name(&self, index: usize) -> anyhow::Result<&'a str> {
-        if self.names[7].len() <= index { bail!("name index out of range"); }
-        if self.names[7][index] == "" { bail!("planet name index {} invalid\n{:?}", index, self.names[7]); }
-        Ok(self.names[7][index])
+    pub fn get_item_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(0, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "item", index })
+    }
+    // recipe, quality and planet are not in decoded prints for now
+    #[allow(dead_code)]
+    pub fn get_recipe_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(1, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "recipe", index })
+    }
+    pub fn get_entity_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(2, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "entity", index })
+    }
+    pub fn get_tile_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(3, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "tile", index })
+    }
+    pub fn get_virtual_signal_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(4, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "virtual signal", index })
+    }
+    pub fn get_fluid_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(5, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "fluid", index })
+    }
+    #[allow(dead_code)]
+    pub fn get_quality_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(6, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "quality", index })
+    }
+    #[allow(dead_code)]
+    pub fn get_planet_name(&self, index: usize) -> Result<Cow<'a, str>, ParseErrorKind> {
+        self.get_name(7, index).ok_or(ParseErrorKind::UnknownNameIndex{ namespace: "planet", index })
+    }
+
+    // reverse lookup, used by serializer
+
+    pub fn get_item_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[0].iter().position(|n| *n == name).ok_or_else(|| anyhow!("item name {} not found", name))
+    }
+    #[allow(dead_code)]
+    pub fn get_recipe_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[1].iter().position(|n| *n == name).ok_or_else(|| anyhow!("recipe name {} not found", name))
+    }
+    pub fn get_entity_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[2].iter().position(|n| *n == name).ok_or_else(|| anyhow!("entity name {} not found", name))
+    }
+    pub fn get_tile_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[3].iter().position(|n| *n == name).ok_or_else(|| anyhow!("tile name {} not found", name))
+    }
+    pub fn get_virtual_signal_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[4].iter().position(|n| *n == name).ok_or_else(|| anyhow!("vsignal name {} not found", name))
+    }
+    pub fn get_fluid_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[5].iter().position(|n| *n == name).ok_or_else(|| anyhow!("fluid name {} not found", name))
+    }
+    #[allow(dead_code)]
+    pub fn get_quality_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[6].iter().position(|n| *n == name).ok_or_else(|| anyhow!("quality name {} not found", name))
+    }
+    #[allow(dead_code)]
+    pub fn get_planet_index(&self, name: &str) -> anyhow::Result<usize> {
+        self.names[7].iter().position(|n| *n == name).ok_or_else(|| anyhow!("planet name {} not found", name))
     }
 }
 
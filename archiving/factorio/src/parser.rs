@@ -1,238 +1,409 @@
 
-use anyhow::{anyhow, bail};
+use std::borrow::Cow;
 use chrono::{DateTime, Utc};
 
 use crate::binary_reader::Reader;
 use crate::blueprint_library::*;
+use crate::error::{ParseErrorKind, ParseResult};
 use crate::name::Names;
+use crate::trace::TraceRecord;
 
 pub struct Parser<'a> {
     base: Reader<'a>,
+    // decided by file version, before that only version itself is read
+    layout: Layout,
+    // slot indexes from library root to current print
+    slot_path: Vec<usize>,
+    // prints not understood and skipped, returned with library
+    skipped: Vec<SkippedPrint<'a>>,
 }
 impl<'a> Parser<'a> {
     pub fn new(base: Reader<'a>) -> Self {
-        Self{ base }
+        Self{ base, layout: Layout::V2, slot_path: Vec::new(), skipped: Vec::new() }
+    }
+    // records of every read if reader is constructed with trace, still available after parse error
+    pub fn take_trace(&mut self) -> Vec<TraceRecord<'a>> {
+        self.base.take_trace()
+    }
+
+    // attach offset and path of the name index just read
+    fn name(&self, name: Result<Cow<'a, str>, ParseErrorKind>) -> ParseResult<Cow<'a, str>> {
+        name.map_err(|kind| self.base.error(kind))
     }
 
-    pub fn parse(&mut self) -> anyhow::Result<BlueprintLibrary<'a>> {
+    pub fn parse(&mut self) -> ParseResult<BlueprintLibrary<'a>> {
 
         let file_version = self.parse_version()?;
+        self.layout = Layout::from_version(file_version)
+            .ok_or_else(|| self.base.error_at(0, ParseErrorKind::Invalid(format!("unsupported file version {file_version:?}"))))?;
 
         self.base.expect(0)?; // mysterious skip
-        self.parse_migrations()?;
+        let migrations = self.parse_migrations()?;
 
         let names = self.parse_global_names()?;
-        // println!("{:?}", names);
 
-        self.base.skip(1)?; // mysterious skip
+        let mysterious_byte = self.base.field("mysterious_byte").read_u8()?; // mysterious skip
         self.base.expect(0)?; // mysterious skip
         // what's generation counter?
-        let _generation_counter = self.base.read_u32()?;
+        let generation_counter = self.base.field("generation_counter").read_u32()?;
 
         // this is 2023/11/12, what's this time? looks like the game install time?
         // this is 2025/1/21, looks like last update time
-        let timestamp = self.base.read_u32()? as i64;
-        let file_timestamp = DateTime::<Utc>::from_timestamp(timestamp, 0).ok_or_else(|| anyhow!("invalid timestamp"))?;
+        let timestamp = self.base.field("timestamp").read_u32()? as i64;
+        let file_timestamp = DateTime::<Utc>::from_timestamp(timestamp, 0)
+            .ok_or_else(|| self.base.error(ParseErrorKind::Invalid(format!("invalid timestamp {timestamp}"))))?;
 
         self.base.expect(1)?; // mysterious skip
 
-        let _print_count = self.base.read_u32()?;
-        // ATTENTION no loop for now, because parsing operation is incomplete, second loop will meet invalid data
-        let print = self.parse_print(&names)?.unwrap();
+        let (print_count, prints) = self.parse_prints(&names)?;
 
-        Ok(BlueprintLibrary{ file_version, file_timestamp, prints: vec![print] })
+        let skipped = std::mem::take(&mut self.skipped);
+        Ok(BlueprintLibrary{ file_version, migrations, names, mysterious_byte, generation_counter,
+            file_timestamp, print_count, prints, skipped })
     }
 
-    fn parse_version(&mut self) -> anyhow::Result<Version> {
-        Ok((self.base.read_u16()?, self.base.read_u16()?, self.base.read_u16()?, self.base.read_u16()?))
+    fn parse_version(&mut self) -> ParseResult<Version> {
+        self.base.enter("version");
+        let version = (self.base.field("major").read_u16()?, self.base.field("minor").read_u16()?,
+            self.base.field("patch").read_u16()?, self.base.field("build").read_u16()?);
+        self.base.leave();
+        Ok(version)
     }
 
     // migrations seems are for mod
     // https://lua-api.factorio.com/latest/auxiliary/migrations.html
-    // not related to me currently so ignore result
-    fn parse_migrations(&mut self) -> anyhow::Result<()> {
-        let migration_count = self.base.read_u8()?;
-        for _ in 0..migration_count {
-            let _mod_name = self.base.read_str()?;
-            let _migration_file = self.base.read_str()?;
+    // not related to me currently, only kept for writing back
+    fn parse_migrations(&mut self) -> ParseResult<Vec<(Cow<'a, str>, Cow<'a, str>)>> {
+        let migration_count = self.base.field("migration_count").read_u8()?;
+        let mut migrations = Vec::with_capacity(migration_count as usize);
+        for index in 0..migration_count as usize {
+            self.base.enter_index("migrations", index);
+            let mod_name = Cow::Borrowed(self.base.field("mod_name").read_str()?);
+            let migration_file = Cow::Borrowed(self.base.field("file").read_str()?);
+            migrations.push((mod_name, migration_file));
+            self.base.leave();
         }
-        Ok(())
+        Ok(migrations)
     }
 
-    fn parse_global_names(&mut self) -> anyhow::Result<Names<'a>> {
+    fn parse_global_names(&mut self) -> ParseResult<Names<'a>> {
         let mut names = Names::new();
 
         // TODO this is definitely 1.0 content,
         // it does not have any 2.0 and spaceage name, and have 1.0 specific name like RCU?
-        let prototype_count = self.base.read_u16()?;
-        for _ in 0..prototype_count {
-            let prototype_name = self.base.read_str()?;
-            // ATTENTION NOT SAME 2/SA seems does not have this difference
-            // self.base.read_u16_unless_then_u8(prototype_name == "tile")?;
-            // ATTENTION NOT SAME 2/SA quality use this mechanism
-            let name_count = self.base.read_u16_unless_then_u8(prototype_name == "quality")?; 
-            for _ in 0..name_count {
-                // ATTENTION NOT SAME 2/SA seems does not have this difference
-                // self.base.read_u16_unless_then_u8(prototype_name == "tile")?;
-                // ATTENTION NOT SAME 2/SA quality use this mechanism
-                let index = self.base.read_u16_unless_then_u8(prototype_name == "quality")?;
-                let name = self.base.read_str()?;
-                // println!("prototype {prototype_name} name {name} index {index}");
-                names.add(index, name, prototype_name)?;
+        let prototype_count = self.base.field("prototype_count").read_u16()?;
+        for prototype_index in 0..prototype_count as usize {
+            self.base.enter_index("prototypes", prototype_index);
+            let prototype_name = self.base.field("prototype").read_str()?;
+            names.add_prototype(prototype_name);
+            // ATTENTION NOT SAME 2/SA 1.1 tile use u8, 2.0 tile use u16 but quality use u8
+            let short_index = self.layout.short_name_index(prototype_name);
+            let name_count = self.base.field("name_count").read_u16_unless_then_u8(short_index)?;
+            for name_index in 0..name_count {
+                self.base.enter_index("names", name_index);
+                let index = self.base.field("index").read_u16_unless_then_u8(short_index)?;
+                let name = self.base.field("name").read_str()?;
+                names.add(index, name, prototype_name).map_err(|kind| self.base.error(kind))?;
+                self.base.leave();
             }
+            self.base.leave();
         }
         Ok(names)
     }
 
+    // print slots in blueprint library or blueprint book, return (slot count, prints)
+    fn parse_prints(&mut self, names: &Names<'a>) -> ParseResult<(usize, Vec<(usize, Print<'a>)>)> {
+        // this count include inactive slots
+        let print_count = self.base.field("print_count").read_u32()?;
+        let mut prints = Vec::new();
+        for index in 0..print_count as usize {
+            self.slot_path.push(index);
+            self.base.enter_index("prints", index);
+            if let Some(print) = self.parse_print(names)? {
+                prints.push((index, print));
+            }
+            self.base.leave();
+            self.slot_path.pop();
+        }
+        Ok((print_count as usize, prints))
+    }
+
     // the blue/green/redprint items in blueprint library or blueprint book
-    fn parse_print(&mut self, names: &Names<'a>) -> anyhow::Result<Option<Print<'a>>> {
-        let active = self.base.read_bool()?;
+    fn parse_print(&mut self, names: &Names<'a>) -> ParseResult<Option<Print<'a>>> {
+        let print_start = self.base.position();
+        let active = self.base.field("active").read_bool()?;
         if !active { return Ok(None); }
 
-        let print_type = match self.base.read_u8()? {
+        let print_type = match self.base.field("type").read_u8()? {
             0 => "blueprint",
             1 => "blueprint-book",
-            2 => "deconstruction-item",
-            3 => "upgrade-item",
-            v => bail!("0x{:x}: unknown print type {v}", self.base.position() - 1),
+            2 => "deconstruction-planner",
+            3 => "upgrade-planner",
+            v => return Err(self.base.unexpected("print type", v)),
         };
 
         // what is generation?
-        let _generation = self.base.read_u32()?;
+        let generation = self.base.field("generation").read_u32()?;
 
-        // this seems redundent data
-        let print_item_index = self.base.read_u16()?;
-        let alternative_print_type = names.get_item_name(print_item_index as usize)?;
+        // this seems redundent data, print type is same as item name, not prototype name
+        let print_item_index = self.base.field("item").read_u16()?;
+        let alternative_print_type = self.name(names.get_item_name(print_item_index as usize))?;
         if print_type != alternative_print_type {
-            bail!("0x{:x}: mismatch print item name '{}' != '{}'",
-                self.base.position() - 2, print_type, alternative_print_type);
+            return Err(self.base.error(ParseErrorKind::Invalid(format!("mismatch print item name '{print_type}' != '{alternative_print_type}'"))));
         }
 
         Ok(Some(match print_type {
-            "blueprint" => Print::Blueprint(self.parse_blueprint(names)?),
-            "blueprint-book" => Print::BlueprintBook(self.parse_blueprint_book(names)?),
-            "deconstruction-item" => Print::DeconstructionPlan(self.parse_deconstruction_plan(names)?),
-            "upgrade-item" => Print::UpgradePlan(self.parse_upgrade_plan(names)?),
+            "blueprint" => match self.parse_blueprint(names, generation, print_start)? {
+                Some(blueprint) => Print::Blueprint(blueprint),
+                None => return Ok(None),
+            },
+            "blueprint-book" => Print::BlueprintBook(self.parse_blueprint_book(names, generation)?),
+            "deconstruction-planner" => Print::DeconstructionPlan(self.parse_deconstruction_plan(names, generation)?),
+            "upgrade-planner" => Print::UpgradePlan(self.parse_upgrade_plan(names, generation)?),
             _ => unreachable!(),
         }))
     }
 
-    fn parse_blueprint(&mut self, names: &Names<'a>) -> anyhow::Result<Blueprint<'a>> {
-        println!("0x{:x} beginning of a blueprint", self.base.position());
-
-        let _label = self.base.read_str()?;
+    // return None if the blueprint content is not understood,
+    // in that case content is skipped by content size and recorded in self.skipped
+    fn parse_blueprint(&mut self, names: &Names<'a>, generation: u32, print_start: usize) -> ParseResult<Option<Blueprint<'a>>> {
+        let label = Cow::Borrowed(self.base.field("label").read_str()?);
         self.base.expect(0)?; // mysterious skip
 
-        let has_removed_mods = self.base.read_bool()?;
+        // binary layout of removed mods is unknown, they are before content size,
+        // so blueprint with them cannot be skipped and stops the whole file
+        let has_removed_mods = self.base.field("has_removed_mods").read_bool()?;
         if has_removed_mods {
-            bail!("not support has removed mods for now");
+            return Err(self.base.error(ParseErrorKind::Unsupported("blueprint with removed mods")));
         }
-        let _content_size = self.base.read_length()?;
+        let content_size = self.base.field("content_size").read_length()?;
+        let content_end = self.base.position() + content_size;
+
+        // content error may leave path entered
+        let path_depth = self.base.path_depth();
+
+        let reason = match self.parse_blueprint_content(names, generation, label.clone()) {
+            Ok(blueprint) if self.base.position() == content_end => return Ok(Some(blueprint)),
+            Ok(_) => format!("0x{:x}: content end mismatch, expect 0x{:x}", self.base.position(), content_end),
+            Err(error) => error.to_string(),
+        };
+        self.base.restore_path(path_depth);
+        self.base.set_position(content_end)?;
+        let raw = Cow::Borrowed(self.base.slice(print_start, content_end)?);
+        self.skipped.push(SkippedPrint{ slot_path: self.slot_path.clone(), label, reason, raw });
+        Ok(None)
+    }
+
+    fn parse_blueprint_content(&mut self, names: &Names<'a>, generation: u32, label: Cow<'a, str>) -> ParseResult<Blueprint<'a>> {
         let version = self.parse_version()?;
 
         self.base.expect(0)?; // mysterious skip
-        self.parse_migrations()?;
+        let migrations = self.parse_migrations()?;
 
-        let description = self.base.read_str()?;
+        let description = Cow::Borrowed(self.base.field("description").read_str()?);
         let snap_to_grid = self.parse_snap_to_grid()?;
 
-        let entity_count = self.base.read_u32()?;
+        let entity_count = self.base.field("entity_count").read_u32()?;
         let mut entities = Vec::<BlueprintEntity<'a>>::with_capacity(entity_count as usize);
-        for _ in 0..entity_count {
-            let entity_name_index = self.base.read_u16()?;
-            let entity_name = names.get_entity_name(entity_name_index as usize)?;
+        for entity_index in 0..entity_count as usize {
+            self.base.enter_index("entities", entity_index);
+            let entity_start = self.base.position();
+            let entity_name_index = self.base.field("name").read_u16()?;
+            let entity_name = self.name(names.get_entity_name(entity_name_index as usize))?;
 
             // position is stored as u32 and use last byte to represent fraction part,
             // amazingly there is no precision error if convert f64, /256 is precise
-        
-            let maybe_offset_x = self.base.read_i16()? as i32;
+            self.base.enter("position");
+            let maybe_offset_x = self.base.field("offset_x").read_i16()? as i32;
             let position = if maybe_offset_x == 0x7FFF {
-                (self.base.read_i32()? as f64 / 256.0, self.base.read_i32()? as f64 / 256.0)
+                (self.base.field("x").read_i32()? as f64 / 256.0, self.base.field("y").read_i32()? as f64 / 256.0)
             } else {
-                let maybe_offset_y = self.base.read_i16()? as i32;
+                let maybe_offset_y = self.base.field("offset_y").read_i16()? as i32;
                 if let Some((last_x, last_y)) = entities.last().map(|e| e.position) {
                     (last_x + maybe_offset_x as f64 / 256.0, last_y + maybe_offset_y as f64 / 256.0)
                 } else {
                     (maybe_offset_x as f64 / 256.0, maybe_offset_y as f64 / 256.0)
                 }
             };
+            self.base.leave();
 
             self.base.expect(0x20)?; // mysterious skip
 
             // this flag only have 0 or 0x10 value
-            let has_entity_id = self.base.read_u8()? & 0x10 == 0x10;
+            let has_entity_id = self.base.field("flags").read_u8()? & 0x10 == 0x10;
             // this is not json format's entity_number, TODO check this value's postprocess
             let entity_id = if has_entity_id {
                 self.base.expect(1)?; // mysterious skip
-                self.base.read_u32()? as usize
+                self.base.field("entity_id").read_u32()? as usize
             } else { 0 };
 
-            let kind = match entity_name {
+            // only layouts seen in real file, other entity kinds are only imported from json, see blueprint_string.rs
+            let kind = match entity_name.as_ref() {
                 "roboport" => EntityKind::Roboport(self.parse_roboport(names)?),
-                "express-underground-belt" => EntityKind::ExpressUndergroundBelt(self.parse_underground_belt()?),
-                _ => bail!("unhandled entity {entity_name}"),
+                "underground-belt" | "fast-underground-belt" | "express-underground-belt" | "turbo-underground-belt" =>
+                    EntityKind::UndergroundBelt(self.parse_underground_belt(BeltTier::from_name(&entity_name))?),
+                _ => return Err(self.base.error_at(entity_start, ParseErrorKind::UnknownEntity(entity_name.into_owned()))),
             };
-            println!("entity {}", kind.name());
 
             // entity contained items, like ammo and robot
             let mut items = Vec::new();
-            let item_type_count = self.base.read_u32()?;
-            for _ in 0..item_type_count {
-                let name_index = self.base.read_u16()?;
-                let name = names.get_item_name(name_index as usize)?;
-                let count = self.base.read_u32()? as usize;
-                items.push((name, count));
+            let item_type_count = self.base.field("item_count").read_u32()?;
+            for item_index in 0..item_type_count as usize {
+                self.base.enter_index("items", item_index);
+                let name_index = self.base.field("name").read_u16()?;
+                let name = self.name(names.get_item_name(name_index as usize))?;
+                let count = self.base.field("count").read_u32()? as usize;
+                items.push((name, None, count));
+                self.base.leave();
             }
 
-            let has_tags = self.base.read_bool()?;
+            let has_tags = self.base.field("has_tags").read_bool()?;
             if has_tags {
-                bail!("not support has tags for now");
+                return Err(self.base.error(ParseErrorKind::Unsupported("tags")));
             }
 
-            entities.push(BlueprintEntity{ kind, position, entity_id, items });
+            // entity and item quality are not seen in real file, only from json
+            entities.push(BlueprintEntity{ kind, position, entity_id, quality: None, items, tags: Vec::new() });
+            self.base.leave();
         }
 
-        Ok(Blueprint{ version, description, snap_to_grid, entities })
+        // icons, tile list, 2.0 wire list and parameters are not seen in real file,
+        // 1.1 wires are circuit connections in entities
+        Ok(Blueprint{ generation, label, version, migrations, description, icons: Vec::new(), snap_to_grid, entities, tiles: Vec::new(),
+            wires: Vec::new(), parameters: Vec::new() })
     }
-    fn parse_blueprint_book(&mut self, _names: &Names<'a>) -> anyhow::Result<BlueprintBook> {
-        Ok(BlueprintBook{})
+
+    fn parse_blueprint_book(&mut self, names: &Names<'a>, generation: u32) -> ParseResult<BlueprintBook<'a>> {
+        let label = Cow::Borrowed(self.base.field("label").read_str()?);
+        let description = Cow::Borrowed(self.base.field("description").read_str()?);
+        let icons = self.parse_icons()?;
+        // books inside book is also handled here
+        let (print_count, prints) = self.parse_prints(names)?;
+        let active_index = self.base.field("active_index").read_u8()? as usize;
+        self.base.expect(0)?; // mysterious skip
+
+        Ok(BlueprintBook{ generation, label, description, icons, active_index, print_count, prints })
     }
-    fn parse_deconstruction_plan(&mut self, _names: &Names<'a>) -> anyhow::Result<DeconstructionPlan> {
-        Ok(DeconstructionPlan{})
+    fn parse_deconstruction_plan(&mut self, names: &Names<'a>, generation: u32) -> ParseResult<DeconstructionPlan<'a>> {
+        let label = Cow::Borrowed(self.base.field("label").read_str()?);
+        let description = Cow::Borrowed(self.base.field("description").read_str()?);
+        let icons = self.parse_icons()?;
+
+        let entity_filter_mode = self.parse_filter_mode()?;
+        let mut entity_filters = Vec::new();
+        let entity_filter_count = self.base.field("entity_filter_count").read_u8()?;
+        for index in 0..entity_filter_count as usize {
+            self.base.enter_index("entity_filters", index);
+            let name_index = self.base.field("name").read_u16()? as usize;
+            // empty slot have name index 0
+            let name = if name_index == 0 { None } else { Some(self.name(names.get_entity_name(name_index))?) };
+            let quality = self.parse_quality_condition()?;
+            if let Some(name) = name {
+                entity_filters.push((index, EntityFilter{ name, quality }));
+            }
+            self.base.leave();
+        }
+        let trees_and_rocks_only = self.base.field("trees_and_rocks_only").read_bool()?;
+
+        let tile_filter_mode = self.parse_filter_mode()?;
+        let tile_selection_mode = match self.base.field("tile_selection_mode").read_u8()? {
+            0 => TileSelectionMode::Normal,
+            1 => TileSelectionMode::Always,
+            2 => TileSelectionMode::Never,
+            3 => TileSelectionMode::Only,
+            v => return Err(self.base.unexpected("tile selection mode", v)),
+        };
+        let mut tile_filters = Vec::new();
+        let tile_filter_count = self.base.field("tile_filter_count").read_u8()?;
+        for index in 0..tile_filter_count as usize {
+            self.base.enter_index("tile_filters", index);
+            let name_index = self.base.field("name").read_u16()? as usize;
+            if name_index != 0 {
+                tile_filters.push((index, self.name(names.get_tile_name(name_index))?));
+            }
+            self.base.leave();
+        }
+
+        Ok(DeconstructionPlan{ generation, label, description, icons, entity_filter_mode, entity_filter_count: entity_filter_count as usize,
+            entity_filters, trees_and_rocks_only, tile_filter_mode, tile_selection_mode, tile_filter_count: tile_filter_count as usize, tile_filters })
     }
-    fn parse_upgrade_plan(&mut self, _names: &Names<'a>) -> anyhow::Result<UpgradePlan> {
-        Ok(UpgradePlan{})
+    fn parse_upgrade_plan(&mut self, names: &Names<'a>, generation: u32) -> ParseResult<UpgradePlan<'a>> {
+        let label = Cow::Borrowed(self.base.field("label").read_str()?);
+        let description = Cow::Borrowed(self.base.field("description").read_str()?);
+        let icons = self.parse_icons()?;
+
+        let mut mappings = Vec::new();
+        let mapping_count = self.base.field("mapping_count").read_u8()?;
+        for index in 0..mapping_count as usize {
+            self.base.enter_index("mappings", index);
+            let kind = match self.base.field("kind").read_u8()? {
+                0 => UpgradeKind::Entity,
+                1 => UpgradeKind::Item,
+                v => return Err(self.base.unexpected("upgrade mapping kind", v)),
+            };
+            let get_name = |name_index| match kind {
+                UpgradeKind::Entity => names.get_entity_name(name_index),
+                UpgradeKind::Item => names.get_item_name(name_index),
+            };
+
+            // empty side have name index 0, but quality is still there
+            self.base.enter("from");
+            let from_index = self.base.field("name").read_u16()? as usize;
+            let from_name = if from_index == 0 { None } else { Some(self.name(get_name(from_index))?) };
+            let from_quality = self.parse_quality_condition()?;
+            let from = from_name.map(|name| (name, from_quality));
+            self.base.leave();
+
+            self.base.enter("to");
+            let to_index = self.base.field("name").read_u16()? as usize;
+            let to_name = if to_index == 0 { None } else { Some(self.name(get_name(to_index))?) };
+            let to_quality = self.parse_to_quality()?;
+            let to = to_name.map(|name| (name, to_quality));
+            self.base.leave();
+
+            mappings.push((index, UpgradeMapping{ kind, from, to }));
+            self.base.leave();
+        }
+
+        Ok(UpgradePlan{ generation, label, description, icons, mapping_count: mapping_count as usize, mappings })
     }
 
-    fn parse_snap_to_grid(&mut self) -> anyhow::Result<Option<SnapToGrid>> {
-        let snap_to_grid = self.base.read_bool()?;
+    fn parse_snap_to_grid(&mut self) -> ParseResult<Option<SnapToGrid>> {
+        let snap_to_grid = self.base.field("snap_to_grid").read_bool()?;
         if !snap_to_grid {
             return Ok(None);
         }
-        let x = self.base.read_u32()?;
-        let y = self.base.read_u32()?;
-        let absolute = self.base.read_bool()?;
+        self.base.enter("snap_to_grid");
+        let x = self.base.field("x").read_u32()?;
+        let y = self.base.field("y").read_u32()?;
+        let absolute = self.base.field("absolute").read_bool()?;
         let (absolute_x, absolute_y) = if absolute {
-            (self.base.read_u32()?, self.base.read_u32()?)
+            (self.base.field("absolute_x").read_u32()?, self.base.field("absolute_y").read_u32()?)
         } else { (0, 0) };
+        self.base.leave();
 
         Ok(Some(SnapToGrid{ size: (x, y), absolute: (absolute_x, absolute_y) }))
     }
 
-    fn parse_circuit_connections(&mut self) -> anyhow::Result<Option<CircuitConnections>> {
-        let has_circuit_connections = self.base.read_bool()?;
+    // ATTENTION NOT SAME 2/SA only in 1.1 entities, see parse_roboport
+    fn parse_circuit_connections(&mut self) -> ParseResult<Option<CircuitConnections>> {
+        let has_circuit_connections = self.base.field("has_circuit_connections").read_bool()?;
         if !has_circuit_connections { return Ok(None); }
 
         let mut connections = CircuitConnections{ red: Vec::new(), green: Vec::new() };
-        let connection_count = self.base.read_u8()?;
-        for _ in 0..connection_count {
-            connections.red.push((self.base.read_u32()? as usize, self.base.read_u8()? as usize));
+        let connection_count = self.base.field("red_count").read_u8()?;
+        for index in 0..connection_count as usize {
+            self.base.enter_index("red", index);
+            connections.red.push((self.base.field("entity_id").read_u32()? as usize, self.base.field("circuit_id").read_u8()? as usize));
             self.base.expect(0xFF)?; // mysterious skip
+            self.base.leave();
         }
-        let connection_count = self.base.read_u8()?;
-        for _ in 0..connection_count {
-            connections.green.push((self.base.read_u32()? as usize, self.base.read_u8()? as usize));
+        let connection_count = self.base.field("green_count").read_u8()?;
+        for index in 0..connection_count as usize {
+            self.base.enter_index("green", index);
+            connections.green.push((self.base.field("entity_id").read_u32()? as usize, self.base.field("circuit_id").read_u8()? as usize));
             self.base.expect(0xFF)?; // mysterious skip
+            self.base.leave();
         }
 
         for _ in 0..9 {
@@ -241,51 +412,99 @@ impl<'a> Parser<'a> {
         Ok(Some(connections))
     }
 
-    fn parse_signal(&mut self, names: &Names<'a>) -> anyhow::Result<Option<Signal<'a>>> {
-        let kind = match self.base.read_u8()? {
+    fn parse_signal(&mut self, names: &Names<'a>) -> ParseResult<Option<Signal<'a>>> {
+        let kind = match self.base.field("kind").read_u8()? {
             0 => SignalKind::Item,
             1 => SignalKind::Fluid,
             2 => SignalKind::Virtual,
-            _ => bail!("invalid signal kind"),
+            v => return Err(self.base.unexpected("signal kind", v)),
         };
-        let name_index = self.base.read_u16()? as usize;
+        let name_index = self.base.field("name").read_u16()? as usize;
         let name = match kind {
             SignalKind::Item => names.get_item_name(name_index),
             SignalKind::Fluid => names.get_fluid_name(name_index),
             SignalKind::Virtual => names.get_virtual_signal_name(name_index),
         };
-        if let Ok(name) = &name {
-            println!("0x{:x} looks like valid signal {:?} {}", self.base.position() - 3, kind, name);
-        }
         // TODO check whether this ignore is actually check index == 0
         Ok(name.ok().map(|n| Signal{ kind, name: n }))
     }
 
-    fn parse_roboport(&mut self, names: &Names<'a>) -> anyhow::Result<Roboport<'a>> {
-        // println!("0x{:x} beginning of a roboport", self.base.position());
+    // signal as a named field of something, for trace path
+    fn parse_signal_field(&mut self, names: &Names<'a>, field: &'static str) -> ParseResult<Option<Signal<'a>>> {
+        self.base.enter(field);
+        let signal = self.parse_signal(names)?;
+        self.base.leave();
+        Ok(signal)
+    }
+
+    fn parse_filter_mode(&mut self) -> ParseResult<FilterMode> {
+        Ok(match self.base.field("filter_mode").read_u8()? {
+            0 => FilterMode::Whitelist,
+            1 => FilterMode::Blacklist,
+            v => return Err(self.base.unexpected("filter mode", v)),
+        })
+    }
+
+    // NEW in 2.0, None for any quality, not exist in 1.1,
+    // binary layout of quality condition is unknown, only a zero byte for no condition is understood
+    fn parse_quality_condition(&mut self) -> ParseResult<Option<QualityCondition<'a>>> {
+        if self.layout < Layout::V2 { return Ok(None); }
+        // ATTENTION INVENTION the zero byte may actually be comparator
+        let has_quality_condition = self.base.field("has_quality_condition").read_bool()?;
+        if has_quality_condition {
+            return Err(self.base.error(ParseErrorKind::Unsupported("quality condition")));
+        }
+        Ok(None)
+    }
+
+    // NEW in 2.0, exact quality like upgrade plan to quality, None for not specified, not exist in 1.1,
+    // binary layout is unknown like quality condition
+    fn parse_to_quality(&mut self) -> ParseResult<Option<Cow<'a, str>>> {
+        if self.layout < Layout::V2 { return Ok(None); }
+        let has_quality = self.base.field("has_quality").read_bool()?;
+        if has_quality {
+            return Err(self.base.error(ParseErrorKind::Unsupported("quality")));
+        }
+        Ok(None)
+    }
+
+    // icon slots of book and planner, binary layout of icons is unknown like blueprint icons,
+    // only a zero icon count for no icons is understood
+    fn parse_icons(&mut self) -> ParseResult<Vec<Option<Signal<'a>>>> {
+        let icon_count = self.base.field("icon_count").read_u8()?;
+        if icon_count != 0 {
+            return Err(self.base.error(ParseErrorKind::Unsupported("icons")));
+        }
+        Ok(Vec::new())
+    }
 
-        // ATTENTION INVENSION this is not here anymore?
-        let circuit_connections = None; // self.parse_circuit_connections()?;
-        let read_logistics = self.base.read_bool()?;
-        let read_robot_stats = self.base.read_bool()?;
+    fn parse_roboport(&mut self, names: &Names<'a>) -> ParseResult<Roboport<'a>> {
+        // ATTENTION NOT SAME 2/SA 1.1 entity have circuit connections here, 2.0 is not here anymore
+        let circuit_connections = if self.layout < Layout::V2 { self.parse_circuit_connections()? } else { None };
+        let read_logistics = self.base.field("read_logistics").read_bool()?;
+        let read_robot_stats = self.base.field("read_robot_stats").read_bool()?;
         // ATTENTION INVENTION this signals not exist when read_robots_stats not exist
-        let available_logistic_output_signal = if read_robot_stats { self.parse_signal(names)? } else { None };
-        let total_logistic_output_signal = if read_robot_stats { self.parse_signal(names)? } else { None };
-        let available_construction_output_signal = if read_robot_stats { self.parse_signal(names)? } else { None };
-        let total_construction_output_signal = if read_robot_stats { self.parse_signal(names)? } else { None };
+        let available_logistic_output_signal = if read_robot_stats { self.parse_signal_field(names, "available_logistic_output_signal")? } else { None };
+        let total_logistic_output_signal = if read_robot_stats { self.parse_signal_field(names, "total_logistic_output_signal")? } else { None };
+        let available_construction_output_signal = if read_robot_stats { self.parse_signal_field(names, "available_construction_output_signal")? } else { None };
+        let total_construction_output_signal = if read_robot_stats { self.parse_signal_field(names, "total_construction_output_signal")? } else { None };
         // NEW in 2.0 TODO check whether this works like this
-        let roboport_count_output_signal = if read_robot_stats { self.parse_signal(names)? } else { None };
+        let roboport_count_output_signal = if read_robot_stats && self.layout >= Layout::V2 {
+            self.parse_signal_field(names, "roboport_count_output_signal")?
+        } else { None };
 
         Ok(Roboport{ circuit_connections, read_logistics, read_robot_stats,
             available_construction_output_signal, available_logistic_output_signal, total_construction_output_signal,
             total_logistic_output_signal, roboport_count_output_signal })
     }
 
-    fn parse_underground_belt(&mut self) -> anyhow::Result<UndergroundBelt> {
-        self.base.expect(0); // mysterious skip
-        let direction = self.base.read_u8()? as usize;
-        let output = self.base.read_bool()?;
-        Ok(UndergroundBelt{ direction, output })
+    // same in 1.1 and 2.0, only express underground belt is seen in real file,
+    // other tiers are same prototype type so assume same layout
+    fn parse_underground_belt(&mut self, tier: BeltTier) -> ParseResult<UndergroundBelt> {
+        self.base.expect(0)?; // mysterious skip
+        let direction = self.base.field("direction").read_u8()? as usize;
+        let output = self.base.field("output").read_bool()?;
+        Ok(UndergroundBelt{ tier, direction, output })
     }
 }
 
@@ -0,0 +1,230 @@
+// render blueprint top-down as svg preview without the game,
+// entities are rectangles of their footprint from built-in size table, belts and inserters have arrow of item flow,
+// tiles are background, snap to grid is grid overlay, label and icons are caption above
+
+use std::fmt::Write;
+
+use crate::blueprint_library::{Blueprint, BlueprintEntity, Signal, SignalKind};
+use crate::entity::EntityKind;
+
+// svg coordinates are in tiles, this is pixels per tile of the output size
+const TILE_PIXELS: f64 = 32.0;
+// caption height above blueprint content, in tiles
+const CAPTION_HEIGHT: f64 = 1.5;
+// space around blueprint content, in tiles
+const MARGIN: f64 = 1.0;
+
+// (width, height) in tiles when facing north, 1x1 for unknown entity
+fn footprint(name: &str) -> (f64, f64) {
+    match name {
+        "splitter" | "fast-splitter" | "express-splitter" | "turbo-splitter" => (2.0, 1.0),
+        "loader" | "fast-loader" | "express-loader" | "turbo-loader" => (1.0, 2.0),
+        "arithmetic-combinator" | "decider-combinator" | "selector-combinator" => (1.0, 2.0),
+        "assembling-machine-1" | "assembling-machine-2" | "assembling-machine-3" | "chemical-plant" | "centrifuge"
+        | "biochamber" | "solar-panel" | "heating-tower" => (3.0, 3.0),
+        "oil-refinery" | "foundry" | "cryogenic-plant" | "nuclear-reactor" => (5.0, 5.0),
+        "roboport" | "electromagnetic-plant" => (4.0, 4.0),
+        "crusher" => (2.0, 3.0),
+        "boiler" | "heat-exchanger" => (3.0, 2.0),
+        "steam-engine" | "steam-turbine" | "fusion-generator" => (3.0, 5.0),
+        "fusion-reactor" => (6.0, 6.0),
+        "big-electric-pole" | "substation" | "power-switch" | "accumulator" | "train-stop" => (2.0, 2.0),
+        // ATTENTION rail shapes are not rectangles, this is their rough bounding box
+        "straight-rail" | "elevated-straight-rail" | "rail-support" => (2.0, 2.0),
+        "half-diagonal-rail" | "elevated-half-diagonal-rail" => (4.0, 4.0),
+        "curved-rail-a" | "elevated-curved-rail-a" | "curved-rail-b" | "elevated-curved-rail-b" => (4.0, 5.0),
+        "curved-rail" => (4.0, 8.0),
+        "rail-ramp" => (4.0, 16.0),
+        "locomotive" | "cargo-wagon" => (2.0, 6.0),
+        "space-platform-hub" => (8.0, 8.0),
+        _ => (1.0, 1.0),
+    }
+}
+
+fn entity_color(kind: &EntityKind) -> &'static str {
+    match kind {
+        EntityKind::TransportBelt(_) | EntityKind::UndergroundBelt(_) | EntityKind::Splitter(_) | EntityKind::Loader(_) => "#c8a028",
+        EntityKind::Inserter(_) => "#3c78c8",
+        EntityKind::CraftingMachine(_) => "#8c8c96",
+        EntityKind::ArithmeticCombinator(_) | EntityKind::DeciderCombinator(_) | EntityKind::ConstantCombinator(_)
+        | EntityKind::SelectorCombinator(_) => "#3ca050",
+        EntityKind::Rail(_) | EntityKind::RailSignal(_) | EntityKind::TrainStop(_) | EntityKind::Locomotive(_)
+        | EntityKind::CargoWagon(_) => "#7a6450",
+        EntityKind::Container(_) | EntityKind::SpacePlatformHub(_) => "#a07850",
+        EntityKind::ElectricPole(_) | EntityKind::PowerSwitch(_) | EntityKind::Accumulator(_) | EntityKind::PowerProducer(_) => "#c86432",
+        EntityKind::Roboport(_) => "#b4b4be",
+        EntityKind::Unknown(_) => "#646464",
+    }
+}
+
+// tiles not listed use the default, like mod tiles
+fn tile_color(name: &str) -> &'static str {
+    match name {
+        "stone-path" => "#6e6458",
+        "concrete" | "hazard-concrete-left" | "hazard-concrete-right" => "#5a5a5a",
+        "refined-concrete" | "refined-hazard-concrete-left" | "refined-hazard-concrete-right" => "#6e6e6e",
+        "landfill" => "#5a4632",
+        "space-platform-foundation" => "#3c3c46",
+        _ => "#50505a",
+    }
+}
+
+// clockwise degrees from north, rolling stocks use orientation, 0 for entity without direction
+fn rotation(entity: &BlueprintEntity, direction_count: usize) -> f64 {
+    match &entity.kind {
+        EntityKind::Locomotive(item) => item.orientation as f64 * 360.0,
+        EntityKind::CargoWagon(item) => item.orientation as f64 * 360.0,
+        kind => kind.direction().map(|direction| direction as f64 * 360.0 / direction_count as f64).unwrap_or(0.0),
+    }
+}
+
+// clockwise degrees from north of item flow, None for entity not moving items
+fn flow_rotation(entity: &BlueprintEntity, direction_count: usize) -> Option<f64> {
+    let rotation = rotation(entity, direction_count);
+    match &entity.kind {
+        EntityKind::TransportBelt(_) | EntityKind::UndergroundBelt(_) | EntityKind::Splitter(_) | EntityKind::Loader(_) => Some(rotation),
+        // ATTENTION NOT SAME 2/SA 1.1 inserter direction is toward pickup position, 2.0 inserter direction is toward drop position
+        EntityKind::Inserter(_) if direction_count == 8 => Some(rotation + 180.0),
+        EntityKind::Inserter(_) => Some(rotation),
+        _ => None,
+    }
+}
+
+// rotated footprint is covered by its bounding square when not right angle
+fn bounding_size(size: (f64, f64), rotation: f64) -> (f64, f64) {
+    let rotation = rotation.rem_euclid(180.0);
+    if rotation == 0.0 {
+        size
+    } else if rotation == 90.0 {
+        (size.1, size.0)
+    } else {
+        (size.0.max(size.1), size.0.max(size.1))
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+// like [item iron-plate], same as rich text in game
+fn icon_text(signal: &Signal) -> String {
+    let kind = match signal.kind {
+        SignalKind::Item => "item",
+        SignalKind::Fluid => "fluid",
+        SignalKind::Virtual => "virtual-signal",
+    };
+    format!("[{kind} {}]", signal.name)
+}
+
+pub fn render_svg(blueprint: &Blueprint) -> String {
+    // ATTENTION NOT SAME 2/SA see EntityKind::direction
+    let direction_count = if blueprint.version.0 >= 2 { 16 } else { 8 };
+
+    // (left, top, right, bottom) of content, tiles position is their top left corner
+    let mut bounds: Option<(f64, f64, f64, f64)> = None;
+    let mut extend = |left: f64, top: f64, right: f64, bottom: f64| {
+        bounds = Some(match bounds {
+            Some((l, t, r, b)) => (l.min(left), t.min(top), r.max(right), b.max(bottom)),
+            None => (left, top, right, bottom),
+        });
+    };
+    for (_, (x, y)) in &blueprint.tiles {
+        extend(*x as f64, *y as f64, *x as f64 + 1.0, *y as f64 + 1.0);
+    }
+    for entity in &blueprint.entities {
+        let (width, height) = bounding_size(footprint(entity.kind.name()), rotation(entity, direction_count));
+        let (x, y) = entity.position;
+        extend(x - width / 2.0, y - height / 2.0, x + width / 2.0, y + height / 2.0);
+    }
+    let (left, top, right, bottom) = bounds.unwrap_or((0.0, 0.0, 1.0, 1.0));
+    let (left, top, right, bottom) = (left.floor() - MARGIN, top.floor() - MARGIN - CAPTION_HEIGHT, right.ceil() + MARGIN, bottom.ceil() + MARGIN);
+    let (width, height) = (right - left, bottom - top);
+
+    let mut result = String::new();
+    writeln!(result, r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{left} {top} {width} {height}" width="{}" height="{}">"#,
+        width * TILE_PIXELS, height * TILE_PIXELS).unwrap();
+    writeln!(result, r##"<rect x="{left}" y="{top}" width="{width}" height="{height}" fill="#202024"/>"##).unwrap();
+
+    writeln!(result, r#"<g class="tiles">"#).unwrap();
+    for (name, (x, y)) in &blueprint.tiles {
+        writeln!(result, r#"<rect x="{x}" y="{y}" width="1" height="1" fill="{}"><title>{}</title></rect>"#, tile_color(name), escape(name)).unwrap();
+    }
+    writeln!(result, "</g>").unwrap();
+
+    writeln!(result, r##"<g class="entities" stroke="#101010" stroke-width="0.05">"##).unwrap();
+    for entity in &blueprint.entities {
+        let (x, y) = entity.position;
+        let (w, h) = footprint(entity.kind.name());
+        let rotation = rotation(entity, direction_count);
+        let transform = if rotation == 0.0 { String::new() } else { format!(r#" transform="rotate({rotation} {x} {y})""#) };
+        // small inset so neighbour entities are distinguishable
+        writeln!(result, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"{transform}><title>{}</title></rect>"#,
+            x - w / 2.0 + 0.05, y - h / 2.0 + 0.05, w - 0.1, h - 0.1, entity_color(&entity.kind), escape(entity.kind.name())).unwrap();
+        if let Some(flow) = flow_rotation(entity, direction_count) {
+            // triangle pointing north before rotation, output underground is hollow
+            let fill = if matches!(&entity.kind, EntityKind::UndergroundBelt(item) if item.output) { "none" } else { "#f0f0f0" };
+            writeln!(result, r##"<polygon class="arrow" points="{},{} {},{} {},{}" fill="{fill}" stroke="#f0f0f0" transform="rotate({flow} {x} {y})"/>"##,
+                x, y - 0.3, x + 0.25, y + 0.2, x - 0.25, y + 0.2).unwrap();
+        }
+    }
+    writeln!(result, "</g>").unwrap();
+
+    // grid lines pass blueprint origin, absolute snapping moves origin by position relative to grid
+    if let Some(snap) = &blueprint.snap_to_grid {
+        let (size_x, size_y) = (snap.size.0.max(1) as f64, snap.size.1.max(1) as f64);
+        let (origin_x, origin_y) = (-(snap.absolute.0 as f64), -(snap.absolute.1 as f64));
+        writeln!(result, r##"<g class="grid" stroke="#e0e0e0" stroke-opacity="0.5" stroke-width="0.08">"##).unwrap();
+        let content_top = top + CAPTION_HEIGHT;
+        let mut x = origin_x + ((left - origin_x) / size_x).ceil() * size_x;
+        while x <= right {
+            writeln!(result, r#"<line x1="{x}" y1="{content_top}" x2="{x}" y2="{bottom}"/>"#).unwrap();
+            x += size_x;
+        }
+        let mut y = origin_y + ((content_top - origin_y) / size_y).ceil() * size_y;
+        while y <= bottom {
+            writeln!(result, r#"<line x1="{left}" y1="{y}" x2="{right}" y2="{y}"/>"#).unwrap();
+            y += size_y;
+        }
+        writeln!(result, "</g>").unwrap();
+    }
+
+    let mut caption = if blueprint.label.is_empty() { "(no label)".to_string() } else { blueprint.label.to_string() };
+    for icon in blueprint.icons.iter().flatten() {
+        caption.push(' ');
+        caption.push_str(&icon_text(icon));
+    }
+    writeln!(result, r##"<text class="caption" x="{}" y="{}" font-size="0.8" font-family="sans-serif" fill="#f0f0f0">{}</text>"##,
+        left + 0.5, top + 1.0, escape(&caption)).unwrap();
+    writeln!(result, "</svg>").unwrap();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blueprint_library::*;
+    use super::*;
+
+    #[test]
+    fn render() {
+        let mut blueprint = Blueprint::new("belts & inserters", (2, 0, 28, 1));
+        blueprint.icons = vec![None, Some(Signal::new(SignalKind::Item, "iron-plate"))];
+        blueprint.snap_to_grid = Some(SnapToGrid{ size: (4, 4), absolute: (0, 0) });
+        blueprint.add_entity(EntityKind::UndergroundBelt(UndergroundBelt{ tier: BeltTier::Normal, direction: 4, output: true }), (0.5, 0.5));
+        blueprint.add_entity(EntityKind::Splitter(Splitter{ tier: BeltTier::Normal, direction: 4, input_priority: SplitterPriority::None,
+            output_priority: SplitterPriority::None, filter: None }), (2.5, 1.0));
+        blueprint.add_entity(EntityKind::Accumulator(Accumulator{ circuit_connections: None, output_signal: None }), (6.0, 1.0));
+        blueprint.add_tile("concrete", (0, 3));
+        // name of entity from mod is not trusted
+        blueprint.add_entity(EntityKind::Unknown(UnknownEntity{ name: "<lamp>".into(), fields: serde_json::Map::new() }), (6.5, 3.5));
+
+        let svg = render_svg(&blueprint);
+        // content is x 0 to 7 and y 0 to 4, with margin and caption
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -2.5 9 7.5" width="288" height="240">"#), "{svg}");
+        assert!(svg.contains(r##"<rect x="0" y="3" width="1" height="1" fill="#5a5a5a"><title>concrete</title></rect>"##));
+        assert!(svg.contains(r##"fill="none" stroke="#f0f0f0" transform="rotate(90 0.5 0.5)"/>"##));
+        assert_eq!(svg.matches(r#"class="arrow""#).count(), 2);
+        assert_eq!(svg.matches("<line ").count(), 5);
+        assert!(svg.contains(">belts &amp; inserters [item iron-plate]</text>"));
+        assert!(svg.contains("<title>&lt;lamp&gt;</title>") && !svg.contains("<lamp>"));
+    }
+}
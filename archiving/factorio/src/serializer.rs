@@ -0,0 +1,788 @@
+// write blueprint library back to blueprint-storage.dat format,
+// every serialize function mirrors the parse function with same name in parser.rs
+
+use std::borrow::Cow;
+use anyhow::{anyhow, bail};
+
+use crate::binary_writer::Writer;
+use crate::blueprint_library::*;
+use crate::name::Names;
+
+pub struct Serializer<'a> {
+    base: Writer,
+    // decided by library file version
+    layout: Layout,
+    // slot indexes from library root to current print
+    slot_path: Vec<usize>,
+    // (slot path, raw content)[] of skipped prints, written back as is
+    skipped: Vec<(&'a [usize], &'a [u8])>,
+}
+impl<'a> Serializer<'a> {
+    pub fn new(base: Writer) -> Self {
+        Self{ base, layout: Layout::V2, slot_path: Vec::new(), skipped: Vec::new() }
+    }
+    pub fn into_inner(self) -> Vec<u8> {
+        self.base.into_inner()
+    }
+
+    pub fn serialize(&mut self, library: &'a BlueprintLibrary) -> anyhow::Result<()> {
+
+        self.layout = Layout::from_version(library.file_version)
+            .ok_or_else(|| anyhow!("unsupported file version {:?}", library.file_version))?;
+        self.serialize_version(library.file_version);
+        self.base.write_u8(0); // mysterious skip
+        self.serialize_migrations(&library.migrations);
+
+        self.serialize_global_names(&library.names);
+
+        self.base.write_u8(library.mysterious_byte); // mysterious skip
+        self.base.write_u8(0); // mysterious skip
+        self.base.write_u32(library.generation_counter);
+        self.base.write_u32(library.file_timestamp.timestamp() as u32);
+        self.base.write_u8(1); // mysterious skip
+
+        self.skipped = library.skipped.iter().map(|s| (s.slot_path.as_slice(), s.raw.as_ref())).collect();
+        self.serialize_prints(&library.names, library.print_count, &library.prints)?;
+        Ok(())
+    }
+
+    fn serialize_version(&mut self, version: Version) {
+        self.base.write_u16(version.0);
+        self.base.write_u16(version.1);
+        self.base.write_u16(version.2);
+        self.base.write_u16(version.3);
+    }
+
+    fn serialize_migrations(&mut self, migrations: &[(Cow<str>, Cow<str>)]) {
+        self.base.write_u8(migrations.len() as u8);
+        for (mod_name, migration_file) in migrations {
+            self.base.write_str(mod_name);
+            self.base.write_str(migration_file);
+        }
+    }
+
+    fn serialize_global_names(&mut self, names: &Names) {
+        self.base.write_u16(names.prototypes().len() as u16);
+        for (prototype_name, entries) in names.prototypes() {
+            self.base.write_str(prototype_name);
+            let short_index = self.layout.short_name_index(prototype_name);
+            self.base.write_u16_unless_then_u8(short_index, entries.len());
+            for (index, name) in entries {
+                self.base.write_u16_unless_then_u8(short_index, *index);
+                self.base.write_str(name);
+            }
+        }
+    }
+
+    fn serialize_prints(&mut self, names: &Names, print_count: usize, prints: &[(usize, Print)]) -> anyhow::Result<()> {
+        if let Some((index, _)) = prints.iter().find(|(index, _)| *index >= print_count) {
+            bail!("print slot index {index} out of range {print_count}");
+        }
+        self.base.write_u32(print_count as u32);
+        for index in 0..print_count {
+            self.slot_path.push(index);
+            if let Some((_, print)) = prints.iter().find(|(i, _)| *i == index) {
+                self.serialize_print(names, print)?;
+            } else if let Some((_, raw)) = self.skipped.iter().find(|(path, _)| *path == self.slot_path.as_slice()) {
+                self.base.write_bytes(raw);
+            } else {
+                self.base.write_bool(false);
+            }
+            self.slot_path.pop();
+        }
+        Ok(())
+    }
+
+    fn serialize_print(&mut self, names: &Names, print: &Print) -> anyhow::Result<()> {
+        self.base.write_bool(true);
+        let (print_type, print_type_name, generation) = match print {
+            Print::Blueprint(blueprint) => (0, "blueprint", blueprint.generation),
+            Print::BlueprintBook(book) => (1, "blueprint-book", book.generation),
+            Print::DeconstructionPlan(plan) => (2, "deconstruction-planner", plan.generation),
+            Print::UpgradePlan(plan) => (3, "upgrade-planner", plan.generation),
+        };
+        self.base.write_u8(print_type);
+        self.base.write_u32(generation);
+        self.base.write_u16(names.get_item_index(print_type_name)? as u16);
+
+        match print {
+            Print::Blueprint(blueprint) => self.serialize_blueprint(names, blueprint),
+            Print::BlueprintBook(book) => self.serialize_blueprint_book(names, book),
+            Print::DeconstructionPlan(plan) => self.serialize_deconstruction_plan(names, plan),
+            Print::UpgradePlan(plan) => self.serialize_upgrade_plan(names, plan),
+        }
+    }
+
+    // content is written to another writer first to get content size
+    fn serialize_blueprint(&mut self, names: &Names, blueprint: &Blueprint) -> anyhow::Result<()> {
+        self.base.write_str(&blueprint.label);
+        self.base.write_u8(0); // mysterious skip
+        self.base.write_bool(false); // no removed mods, see Parser::parse_blueprint
+
+        let outer = std::mem::replace(&mut self.base, Writer::new());
+        let result = self.serialize_blueprint_content(names, blueprint);
+        let content = std::mem::replace(&mut self.base, outer).into_inner();
+        result?;
+
+        self.base.write_length(content.len());
+        self.base.write_bytes(&content);
+        Ok(())
+    }
+
+    fn serialize_blueprint_content(&mut self, names: &Names, blueprint: &Blueprint) -> anyhow::Result<()> {
+        self.serialize_version(blueprint.version);
+        self.base.write_u8(0); // mysterious skip
+        self.serialize_migrations(&blueprint.migrations);
+
+        self.base.write_str(&blueprint.description);
+        // parsed from json, see Parser::parse_blueprint_content
+        if !blueprint.icons.is_empty() { bail!("binary layout of blueprint icons is unknown"); }
+        self.serialize_snap_to_grid(&blueprint.snap_to_grid);
+
+        self.base.write_u32(blueprint.entities.len() as u32);
+        let mut last_position = None;
+        for entity in &blueprint.entities {
+            // parsed from json, not seen in real file, see Parser::parse_blueprint_content
+            if !matches!(entity.kind, EntityKind::Roboport(_) | EntityKind::UndergroundBelt(_)) {
+                bail!("binary layout of {} is unknown", entity.kind.name());
+            }
+            self.base.write_u16(names.get_entity_index(entity.kind.name())? as u16);
+
+            // use offset from last entity if fit in i16, 0x7FFF is reserved for absolute position
+            let (x, y) = ((entity.position.0 * 256.0) as i64, (entity.position.1 * 256.0) as i64);
+            let (last_x, last_y) = last_position.unwrap_or((0, 0));
+            let (offset_x, offset_y) = (x - last_x, y - last_y);
+            if (i16::MIN as i64..0x7FFF).contains(&offset_x) && (i16::MIN as i64..=i16::MAX as i64).contains(&offset_y) {
+                self.base.write_i16(offset_x as i16);
+                self.base.write_i16(offset_y as i16);
+            } else {
+                self.base.write_i16(0x7FFF);
+                self.base.write_i32(x as i32);
+                self.base.write_i32(y as i32);
+            }
+            last_position = Some((x, y));
+
+            self.base.write_u8(0x20); // mysterious skip
+            if entity.entity_id != 0 {
+                self.base.write_u8(0x10);
+                self.base.write_u8(1); // mysterious skip
+                self.base.write_u32(entity.entity_id as u32);
+            } else {
+                self.base.write_u8(0);
+            }
+            // only imported from json, see Parser::parse_blueprint_content
+            if entity.quality.is_some() { bail!("binary layout of entity quality is unknown"); }
+
+            match &entity.kind {
+                EntityKind::Roboport(roboport) => self.serialize_roboport(names, roboport)?,
+                EntityKind::UndergroundBelt(belt) => self.serialize_underground_belt(belt),
+                _ => unreachable!(),
+            }
+
+            self.base.write_u32(entity.items.len() as u32);
+            for (name, quality, count) in &entity.items {
+                if quality.is_some() { bail!("binary layout of item quality is unknown"); }
+                self.base.write_u16(names.get_item_index(name)? as u16);
+                self.base.write_u32(*count as u32);
+            }
+
+            if !entity.tags.is_empty() { bail!("binary layout of entity tags is unknown"); }
+            self.base.write_bool(false);
+        }
+
+        // parsed from json, see Parser::parse_blueprint_content
+        if !blueprint.tiles.is_empty() { bail!("binary layout of tile list is unknown"); }
+        if !blueprint.wires.is_empty() { bail!("binary layout of wire list is unknown"); }
+        if !blueprint.parameters.is_empty() { bail!("binary layout of blueprint parameters is unknown"); }
+        Ok(())
+    }
+
+    fn serialize_blueprint_book(&mut self, names: &Names, book: &BlueprintBook) -> anyhow::Result<()> {
+        self.base.write_str(&book.label);
+        self.base.write_str(&book.description);
+        self.serialize_icons(&book.icons)?;
+        self.serialize_prints(names, book.print_count, &book.prints)?;
+        self.base.write_u8(book.active_index as u8);
+        self.base.write_u8(0); // mysterious skip
+        Ok(())
+    }
+
+    fn serialize_deconstruction_plan(&mut self, names: &Names, plan: &DeconstructionPlan) -> anyhow::Result<()> {
+        self.base.write_str(&plan.label);
+        self.base.write_str(&plan.description);
+        self.serialize_icons(&plan.icons)?;
+
+        self.serialize_filter_mode(&plan.entity_filter_mode);
+        if let Some((index, _)) = plan.entity_filters.iter().find(|(index, _)| *index >= plan.entity_filter_count) {
+            bail!("entity filter slot index {index} out of range {}", plan.entity_filter_count);
+        }
+        self.base.write_u8(plan.entity_filter_count as u8);
+        for index in 0..plan.entity_filter_count {
+            match plan.entity_filters.iter().find(|(i, _)| *i == index) {
+                Some((_, filter)) => {
+                    self.base.write_u16(names.get_entity_index(&filter.name)? as u16);
+                    self.serialize_quality_condition(&filter.quality)?;
+                },
+                None => {
+                    self.base.write_u16(0);
+                    self.serialize_quality_condition(&None)?;
+                },
+            }
+        }
+        self.base.write_bool(plan.trees_and_rocks_only);
+
+        self.serialize_filter_mode(&plan.tile_filter_mode);
+        self.base.write_u8(match plan.tile_selection_mode {
+            TileSelectionMode::Normal => 0,
+            TileSelectionMode::Always => 1,
+            TileSelectionMode::Never => 2,
+            TileSelectionMode::Only => 3,
+        });
+        if let Some((index, _)) = plan.tile_filters.iter().find(|(index, _)| *index >= plan.tile_filter_count) {
+            bail!("tile filter slot index {index} out of range {}", plan.tile_filter_count);
+        }
+        self.base.write_u8(plan.tile_filter_count as u8);
+        for index in 0..plan.tile_filter_count {
+            match plan.tile_filters.iter().find(|(i, _)| *i == index) {
+                Some((_, name)) => self.base.write_u16(names.get_tile_index(name)? as u16),
+                None => self.base.write_u16(0),
+            }
+        }
+        Ok(())
+    }
+
+    fn serialize_upgrade_plan(&mut self, names: &Names, plan: &UpgradePlan) -> anyhow::Result<()> {
+        self.base.write_str(&plan.label);
+        self.base.write_str(&plan.description);
+        self.serialize_icons(&plan.icons)?;
+
+        if let Some((index, _)) = plan.mappings.iter().find(|(index, _)| *index >= plan.mapping_count) {
+            bail!("upgrade mapping slot index {index} out of range {}", plan.mapping_count);
+        }
+        self.base.write_u8(plan.mapping_count as u8);
+        for index in 0..plan.mapping_count {
+            let Some((_, mapping)) = plan.mappings.iter().find(|(i, _)| *i == index) else {
+                self.base.write_u8(0);
+                self.base.write_u16(0);
+                self.serialize_quality_condition(&None)?;
+                self.base.write_u16(0);
+                self.serialize_to_quality(&None)?;
+                continue;
+            };
+            self.base.write_u8(match mapping.kind {
+                UpgradeKind::Entity => 0,
+                UpgradeKind::Item => 1,
+            });
+            let get_index = |name| match mapping.kind {
+                UpgradeKind::Entity => names.get_entity_index(name),
+                UpgradeKind::Item => names.get_item_index(name),
+            };
+
+            match &mapping.from {
+                Some((name, quality)) => {
+                    self.base.write_u16(get_index(name)? as u16);
+                    self.serialize_quality_condition(quality)?;
+                },
+                None => {
+                    self.base.write_u16(0);
+                    self.serialize_quality_condition(&None)?;
+                },
+            }
+            match &mapping.to {
+                Some((name, quality)) => {
+                    self.base.write_u16(get_index(name)? as u16);
+                    self.serialize_to_quality(quality)?;
+                },
+                None => {
+                    self.base.write_u16(0);
+                    self.serialize_to_quality(&None)?;
+                },
+            }
+        }
+        Ok(())
+    }
+
+    // upgrade target quality, not exist in 1.1, only imported from json, see Parser::parse_to_quality
+    fn serialize_to_quality(&mut self, quality: &Option<Cow<str>>) -> anyhow::Result<()> {
+        if self.layout < Layout::V2 {
+            if quality.is_some() { bail!("quality not supported in 1.1"); }
+            return Ok(());
+        }
+        if quality.is_some() { bail!("binary layout of quality is unknown"); }
+        self.base.write_bool(false);
+        Ok(())
+    }
+
+    fn serialize_snap_to_grid(&mut self, snap_to_grid: &Option<SnapToGrid>) {
+        let Some(snap) = snap_to_grid else {
+            self.base.write_bool(false);
+            return;
+        };
+        self.base.write_bool(true);
+        self.base.write_u32(snap.size.0);
+        self.base.write_u32(snap.size.1);
+        self.base.write_bool(snap.absolute != (0, 0));
+        if snap.absolute != (0, 0) {
+            self.base.write_u32(snap.absolute.0);
+            self.base.write_u32(snap.absolute.1);
+        }
+    }
+
+    // empty signal is written as item with index 0
+    fn serialize_signal(&mut self, names: &Names, signal: &Option<Signal>) -> anyhow::Result<()> {
+        let Some(signal) = signal else {
+            self.base.write_u8(0);
+            self.base.write_u16(0);
+            return Ok(());
+        };
+        let (kind, name_index) = match signal.kind {
+            SignalKind::Item => (0, names.get_item_index(&signal.name)?),
+            SignalKind::Fluid => (1, names.get_fluid_index(&signal.name)?),
+            SignalKind::Virtual => (2, names.get_virtual_signal_index(&signal.name)?),
+        };
+        self.base.write_u8(kind);
+        self.base.write_u16(name_index as u16);
+        Ok(())
+    }
+
+    fn serialize_filter_mode(&mut self, mode: &FilterMode) {
+        self.base.write_u8(match mode {
+            FilterMode::Whitelist => 0,
+            FilterMode::Blacklist => 1,
+        });
+    }
+
+    // only imported from json, see Parser::parse_quality_condition
+    fn serialize_quality_condition(&mut self, condition: &Option<QualityCondition>) -> anyhow::Result<()> {
+        if self.layout < Layout::V2 {
+            if condition.is_some() { bail!("quality not supported in 1.1"); }
+            return Ok(());
+        }
+        if condition.is_some() { bail!("binary layout of quality condition is unknown"); }
+        self.base.write_bool(false);
+        Ok(())
+    }
+
+    // only imported from json, see Parser::parse_icons
+    fn serialize_icons(&mut self, icons: &[Option<Signal>]) -> anyhow::Result<()> {
+        if !icons.is_empty() { bail!("binary layout of icons is unknown"); }
+        self.base.write_u8(0);
+        Ok(())
+    }
+
+    fn serialize_circuit_connections(&mut self, connections: &Option<CircuitConnections>) {
+        let Some(connections) = connections else {
+            self.base.write_bool(false);
+            return;
+        };
+        self.base.write_bool(true);
+        for wires in [&connections.red, &connections.green] {
+            self.base.write_u8(wires.len() as u8);
+            for (entity_id, circuit_id) in wires {
+                self.base.write_u32(*entity_id as u32);
+                self.base.write_u8(*circuit_id as u8);
+                self.base.write_u8(0xFF); // mysterious skip
+            }
+        }
+        for _ in 0..9 {
+            self.base.write_u8(0); // mysterious skip
+        }
+    }
+
+    fn serialize_roboport(&mut self, names: &Names, roboport: &Roboport) -> anyhow::Result<()> {
+        if self.layout < Layout::V2 {
+            self.serialize_circuit_connections(&roboport.circuit_connections);
+        }
+        self.base.write_bool(roboport.read_logistics);
+        self.base.write_bool(roboport.read_robot_stats);
+        if roboport.read_robot_stats {
+            self.serialize_signal(names, &roboport.available_logistic_output_signal)?;
+            self.serialize_signal(names, &roboport.total_logistic_output_signal)?;
+            self.serialize_signal(names, &roboport.available_construction_output_signal)?;
+            self.serialize_signal(names, &roboport.total_construction_output_signal)?;
+            if self.layout >= Layout::V2 {
+                self.serialize_signal(names, &roboport.roboport_count_output_signal)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn serialize_underground_belt(&mut self, belt: &UndergroundBelt) {
+        self.base.write_u8(0); // mysterious skip
+        self.base.write_u8(belt.direction as u8);
+        self.base.write_bool(belt.output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use crate::binary_reader::Reader;
+    use crate::parser::Parser;
+    use super::*;
+
+    fn names() -> Names<'static> {
+        let mut names = Names::new();
+        for (prototype_name, entries) in [
+            ("blueprint", &[(1, "blueprint")][..]),
+            ("blueprint-book", &[(2, "blueprint-book")]),
+            ("deconstruction-item", &[(3, "deconstruction-planner")]),
+            ("upgrade-item", &[(4, "upgrade-planner")]),
+            ("item", &[(5, "construction-robot"), (6, "speed-module"), (7, "speed-module-2")]),
+            ("roboport", &[(1, "roboport")]),
+            ("underground-belt", &[(2, "express-underground-belt"), (3, "underground-belt")]),
+            ("tile", &[(1, "landfill")]),
+            ("virtual-signal", &[(1, "signal-X"), (2, "signal-Y")]),
+            ("quality", &[(1, "normal"), (2, "legendary")]),
+        ] {
+            names.add_prototype(prototype_name);
+            for (index, name) in entries {
+                names.add(*index, *name, prototype_name).unwrap();
+            }
+        }
+        names
+    }
+
+    fn library() -> BlueprintLibrary<'static> {
+        let mut blueprint = Blueprint::new("robots", (2, 0, 28, 1));
+        blueprint.set_description("roboport and belts").set_snap_to_grid((4, 4), Some((1, 2)));
+        blueprint.migrations.push(("base".into(), "2.0.0.json".into()));
+        blueprint.add_entity(EntityKind::Roboport(Roboport{
+            circuit_connections: None,
+            read_logistics: true,
+            read_robot_stats: true,
+            available_logistic_output_signal: Some(Signal::new(SignalKind::Virtual, "signal-X")),
+            total_logistic_output_signal: Some(Signal::new(SignalKind::Virtual, "signal-Y")),
+            available_construction_output_signal: None,
+            total_construction_output_signal: Some(Signal::new(SignalKind::Item, "construction-robot")),
+            roboport_count_output_signal: None,
+        }), (2.0, 2.0)).add_item("construction-robot", 50).entity_id = 1;
+        // far enough to use absolute position
+        blueprint.add_entity(EntityKind::UndergroundBelt(UndergroundBelt{ tier: BeltTier::Express, direction: 4, output: false }), (300.5, -0.5));
+        blueprint.add_entity(EntityKind::UndergroundBelt(UndergroundBelt{ tier: BeltTier::Express, direction: 4, output: true }), (305.5, -0.5))
+            .add_item("speed-module", 2);
+
+        let deconstruction_plan = DeconstructionPlan{
+            generation: 8,
+            label: "landfill".into(),
+            description: "".into(),
+            icons: Vec::new(),
+            entity_filter_mode: FilterMode::Blacklist,
+            // trailing empty slots are kept
+            entity_filter_count: 5,
+            entity_filters: vec![
+                (0, EntityFilter{ name: "roboport".into(), quality: None }),
+                (2, EntityFilter{ name: "express-underground-belt".into(), quality: None }),
+            ],
+            trees_and_rocks_only: false,
+            tile_filter_mode: FilterMode::Whitelist,
+            tile_selection_mode: TileSelectionMode::Only,
+            tile_filter_count: 3,
+            tile_filters: vec![(1, "landfill".into())],
+        };
+        let upgrade_plan = UpgradePlan{
+            generation: 9,
+            label: "upgrade".into(),
+            description: "belt and module".into(),
+            icons: Vec::new(),
+            // trailing empty slots are kept, with kind of empty slot
+            mapping_count: 6,
+            mappings: vec![
+                (0, UpgradeMapping{ kind: UpgradeKind::Entity,
+                    from: Some(("underground-belt".into(), None)), to: Some(("express-underground-belt".into(), None)) }),
+                (3, UpgradeMapping{ kind: UpgradeKind::Item,
+                    from: Some(("speed-module".into(), None)), to: Some(("speed-module-2".into(), None)) }),
+                (4, UpgradeMapping{ kind: UpgradeKind::Item, from: None, to: None }),
+            ],
+        };
+        let mut book = BlueprintBook::new("book");
+        book.set_description("nested");
+        book.print_count = 1;
+        book.add_print(Print::UpgradePlan(upgrade_plan));
+        book.add_print(Print::BlueprintBook(BlueprintBook::new("empty book")));
+        book.print_count += 1;
+        book.active_index = 1;
+
+        BlueprintLibrary{
+            file_version: (2, 0, 28, 1),
+            migrations: Vec::new(),
+            names: names(),
+            mysterious_byte: 0,
+            generation_counter: 42,
+            file_timestamp: DateTime::from_timestamp(1737417600, 0).unwrap(),
+            print_count: 5,
+            prints: vec![(0, Print::Blueprint(blueprint)), (1, Print::DeconstructionPlan(deconstruction_plan)), (3, Print::BlueprintBook(book))],
+            skipped: Vec::new(),
+        }
+    }
+
+    fn serialize(library: &BlueprintLibrary) -> Vec<u8> {
+        let mut serializer = Serializer::new(Writer::new());
+        serializer.serialize(library).unwrap();
+        serializer.into_inner()
+    }
+
+    #[test]
+    fn round_trip() {
+        let library = library();
+        let original = serialize(&library);
+
+        let parsed = Parser::new(Reader::new(&original)).parse().unwrap();
+        assert!(parsed.skipped.is_empty(), "{:?}", parsed.skipped);
+        assert_eq!(format!("{:?}", parsed), format!("{:?}", library));
+        assert_eq!(serialize(&parsed), original);
+        // owned model is still same
+        assert_eq!(serialize(&parsed.into_owned()), original);
+
+        // entities without known binary layout cannot be written
+        let mut unknown = self::library();
+        let Print::Blueprint(blueprint) = &mut unknown.prints[0].1 else { panic!("not blueprint") };
+        blueprint.add_entity(EntityKind::Loader(Loader{ tier: BeltTier::Express, direction: 4, output: true }), (308.5, 1.0));
+        let error = Serializer::new(Writer::new()).serialize(&unknown).unwrap_err();
+        assert_eq!(error.to_string(), "binary layout of express-loader is unknown");
+        let mut unknown = self::library();
+        let Print::Blueprint(blueprint) = &mut unknown.prints[0].1 else { panic!("not blueprint") };
+        blueprint.entities[0].set_quality("legendary");
+        assert!(Serializer::new(Writer::new()).serialize(&unknown).is_err());
+
+        // neither are icons and quality in planners
+        let mut unknown = self::library();
+        let Print::BlueprintBook(book) = &mut unknown.prints[2].1 else { panic!("not book") };
+        book.icons.push(Some(Signal::new(SignalKind::Item, "blueprint-book")));
+        assert!(Serializer::new(Writer::new()).serialize(&unknown).is_err());
+        let mut unknown = self::library();
+        let Print::DeconstructionPlan(plan) = &mut unknown.prints[1].1 else { panic!("not deconstruction plan") };
+        plan.entity_filters[1].1.quality = Some(QualityCondition{ comparator: Comparator::LessThan, quality: "legendary".into() });
+        assert!(Serializer::new(Writer::new()).serialize(&unknown).is_err());
+    }
+
+    // 1.1 layout, no quality, circuit connections in entity
+    fn library_1_1() -> BlueprintLibrary<'static> {
+        let mut blueprint = Blueprint::new("robots", (1, 1, 110, 0));
+        blueprint.add_entity(EntityKind::Roboport(Roboport{
+            circuit_connections: Some(CircuitConnections{ red: vec![(2, 1)], green: Vec::new() }),
+            read_logistics: false,
+            read_robot_stats: true,
+            available_logistic_output_signal: Some(Signal::new(SignalKind::Virtual, "signal-X")),
+            total_logistic_output_signal: None,
+            available_construction_output_signal: None,
+            total_construction_output_signal: None,
+            roboport_count_output_signal: None,
+        }), (2.0, 2.0)).entity_id = 1;
+        blueprint.add_entity(EntityKind::UndergroundBelt(UndergroundBelt{ tier: BeltTier::Express, direction: 2, output: true }), (5.5, 2.5)).entity_id = 2;
+
+        let deconstruction_plan = DeconstructionPlan{
+            generation: 3,
+            label: "trees".into(),
+            description: "".into(),
+            icons: Vec::new(),
+            entity_filter_mode: FilterMode::Whitelist,
+            entity_filter_count: 3,
+            entity_filters: vec![(1, EntityFilter{ name: "roboport".into(), quality: None })],
+            trees_and_rocks_only: true,
+            tile_filter_mode: FilterMode::Whitelist,
+            tile_selection_mode: TileSelectionMode::Never,
+            tile_filter_count: 2,
+            tile_filters: vec![(0, "landfill".into())],
+        };
+        let upgrade_plan = UpgradePlan{
+            generation: 4,
+            label: "belt".into(),
+            description: "".into(),
+            icons: Vec::new(),
+            mapping_count: 3,
+            mappings: vec![(1, UpgradeMapping{ kind: UpgradeKind::Entity,
+                from: Some(("underground-belt".into(), None)), to: Some(("express-underground-belt".into(), None)) })],
+        };
+
+        BlueprintLibrary{
+            file_version: (1, 1, 110, 0),
+            migrations: Vec::new(),
+            names: names(),
+            mysterious_byte: 0,
+            generation_counter: 5,
+            file_timestamp: DateTime::from_timestamp(1699747200, 0).unwrap(),
+            print_count: 3,
+            prints: vec![(0, Print::Blueprint(blueprint)), (1, Print::DeconstructionPlan(deconstruction_plan)), (2, Print::UpgradePlan(upgrade_plan))],
+            skipped: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn round_trip_1_1() {
+        let library = library_1_1();
+        let original = serialize(&library);
+        // tile name count and index are u8 in 1.1
+        assert!(original.windows(11).any(|w| w == b"\x01\x01\x08landfill"));
+
+        let parsed = Parser::new(Reader::new(&original)).parse().unwrap();
+        assert!(parsed.skipped.is_empty(), "{:?}", parsed.skipped);
+        assert_eq!(format!("{:?}", parsed), format!("{:?}", library));
+        assert_eq!(serialize(&parsed), original);
+
+        // quality cannot be written in 1.1 layout
+        let mut with_quality = library_1_1();
+        let Print::DeconstructionPlan(plan) = &mut with_quality.prints[1].1 else { panic!("not deconstruction plan") };
+        plan.entity_filters[0].1.quality = Some(QualityCondition{ comparator: Comparator::Equal, quality: "normal".into() });
+        assert!(Serializer::new(Writer::new()).serialize(&with_quality).is_err());
+    }
+
+    #[test]
+    fn round_trip_skipped() {
+        // rename roboport in name table so that it is not understood
+        let mut original = serialize(&library());
+        // (index 1, length 8, name), not the prototype name before it
+        let name_offset = original.windows(11).position(|w| w == b"\x01\x00\x08roboport").unwrap();
+        original[name_offset + 10] = b'x';
+
+        let parsed = Parser::new(Reader::new(&original)).parse().unwrap();
+        assert_eq!(parsed.skipped.len(), 1);
+        assert_eq!(parsed.skipped[0].slot_path, [0]);
+        assert_eq!(parsed.skipped[0].label, "robots");
+        assert_eq!(serialize(&parsed), original);
+    }
+
+    // hand written bytes of the layout seen in real file, not produced by serializer
+    #[test]
+    fn known_layout() {
+        let original = [
+            // file version 2.0.28.1, skip, no migrations
+            &b"\x02\x00\x00\x00\x1c\x00\x01\x00\x00\x00"[..],
+            // 4 prototypes, each (name, count, (index, name)[])
+            b"\x04\x00\x09blueprint\x01\x00\x01\x00\x09blueprint",
+            b"\x04item\x01\x00\x05\x00\x12construction-robot",
+            b"\x08roboport\x01\x00\x01\x00\x08roboport",
+            b"\x10underground-belt\x01\x00\x02\x00\x18express-underground-belt",
+            // mysterious byte, skip, generation counter, timestamp, skip
+            b"\x00\x00\x2a\x00\x00\x00\x80\xe3\x8e\x67\x01",
+            // 3 slots, active blueprint, generation 7, item blueprint, label, skip, no removed mods, content size
+            b"\x03\x00\x00\x00\x01\x00\x07\x00\x00\x00\x01\x00\x06robots\x00\x00\x4f",
+            // version, skip, no migrations, no description, no snap to grid, 2 entities
+            b"\x02\x00\x00\x00\x1c\x00\x01\x00\x00\x00\x00\x00\x02\x00\x00\x00",
+            // roboport at offset (2, 2), skip, has entity id 1
+            b"\x01\x00\x00\x02\x00\x02\x20\x10\x01\x01\x00\x00\x00",
+            // read logistics, read robot stats, 5 signals, first is item construction-robot
+            b"\x01\x01\x00\x05\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
+            // 50 construction-robot, no tags
+            b"\x01\x00\x00\x00\x05\x00\x32\x00\x00\x00\x00",
+            // express underground belt at absolute (300.5, -0.5), no entity id
+            b"\x02\x00\xff\x7f\x80\x2c\x01\x00\x80\xff\xff\xff\x20\x00",
+            // skip, direction 4, output, no items, no tags
+            b"\x00\x04\x01\x00\x00\x00\x00\x00",
+            // active blueprint with tags, not understood
+            b"\x01\x00\x08\x00\x00\x00\x01\x00\x04tags\x00\x00\x23",
+            b"\x02\x00\x00\x00\x1c\x00\x01\x00\x00\x00\x00\x00\x01\x00\x00\x00",
+            b"\x02\x00\x00\x00\x00\x00\x20\x00\x00\x02\x00\x00\x00\x00\x00\x01\x01\x02\x03",
+            // inactive slot
+            b"\x00",
+        ].concat();
+
+        let parsed = Parser::new(Reader::new(&original)).parse().unwrap();
+        assert_eq!(parsed.file_version, (2, 0, 28, 1));
+        assert_eq!(parsed.generation_counter, 42);
+        assert_eq!(parsed.file_timestamp.timestamp(), 1737417600);
+        assert_eq!(parsed.print_count, 3);
+        assert_eq!(parsed.prints.len(), 1);
+        let (0, Print::Blueprint(blueprint)) = &parsed.prints[0] else { panic!("not blueprint") };
+        assert_eq!((blueprint.generation, blueprint.label.as_ref(), blueprint.version), (7, "robots", (2, 0, 28, 1)));
+        assert!(blueprint.snap_to_grid.is_none());
+        assert_eq!(blueprint.entities.len(), 2);
+
+        let roboport = &blueprint.entities[0];
+        assert_eq!((roboport.position, roboport.entity_id), ((2.0, 2.0), 1));
+        let EntityKind::Roboport(kind) = &roboport.kind else { panic!("not roboport") };
+        assert!(kind.read_logistics && kind.read_robot_stats);
+        assert_eq!(kind.available_logistic_output_signal.as_ref().map(|s| s.name.as_ref()), Some("construction-robot"));
+        assert!(kind.total_logistic_output_signal.is_none() && kind.roboport_count_output_signal.is_none());
+        assert_eq!(roboport.items, [("construction-robot".into(), None, 50)]);
+
+        let belt = &blueprint.entities[1];
+        assert_eq!((belt.position, belt.entity_id), ((300.5, -0.5), 0));
+        let EntityKind::UndergroundBelt(kind) = &belt.kind else { panic!("not underground belt") };
+        assert_eq!((kind.tier, kind.direction, kind.output), (BeltTier::Express, 4, true));
+        assert!(belt.items.is_empty());
+
+        assert_eq!(parsed.skipped.len(), 1);
+        assert_eq!((parsed.skipped[0].slot_path.as_slice(), parsed.skipped[0].label.as_ref()), (&[1][..], "tags"));
+        assert!(parsed.skipped[0].reason.contains("tags"), "{}", parsed.skipped[0].reason);
+
+        assert_eq!(serialize(&parsed), original);
+
+        // removed mods are before content size, the whole file cannot be read
+        let mut removed_mods = original.clone();
+        let offset = removed_mods.windows(9).position(|w| w == b"robots\x00\x00\x4f").unwrap();
+        removed_mods[offset + 7] = 1;
+        let error = Parser::new(Reader::new(&removed_mods)).parse().unwrap_err();
+        assert_eq!(error.path, "prints[0].has_removed_mods");
+        assert!(error.to_string().contains("removed mods"), "{}", error);
+    }
+
+    // hand written bytes of book and planners with empty slots, not produced by serializer,
+    // the game keeps empty slots after the last used one, so slot counts must be kept for same bytes
+    #[test]
+    fn known_layout_empty_slots() {
+        let original = [
+            // file version 2.0.28.1, skip, no migrations
+            &b"\x02\x00\x00\x00\x1c\x00\x01\x00\x00\x00"[..],
+            // 6 prototypes, each (name, count, (index, name)[])
+            b"\x06\x00\x0eblueprint-book\x01\x00\x02\x00\x0eblueprint-book",
+            b"\x13deconstruction-item\x01\x00\x03\x00\x16deconstruction-planner",
+            b"\x0cupgrade-item\x01\x00\x04\x00\x0fupgrade-planner",
+            b"\x08roboport\x01\x00\x01\x00\x08roboport",
+            b"\x10underground-belt\x02\x00\x02\x00\x18express-underground-belt\x03\x00\x10underground-belt",
+            b"\x04tile\x01\x00\x01\x00\x08landfill",
+            // mysterious byte, skip, generation counter, timestamp, skip
+            b"\x00\x00\x2a\x00\x00\x00\x80\xe3\x8e\x67\x01",
+            // 2 slots, active book, generation 5, item blueprint-book, label, no description, no icons, 3 slots
+            b"\x02\x00\x00\x00\x01\x01\x05\x00\x00\x00\x02\x00\x04book\x00\x00\x03\x00\x00\x00",
+            // active deconstruction planner, generation 6, item deconstruction-planner, label, no description, no icons
+            b"\x01\x02\x06\x00\x00\x00\x03\x00\x05trees\x00\x00",
+            // blacklist, 4 entity filter slots of (name, no quality condition), only second is roboport
+            b"\x01\x04\x00\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00",
+            // not trees and rocks only, whitelist, only tiles, 3 tile filter slots, only first is landfill
+            b"\x00\x00\x03\x03\x01\x00\x00\x00\x00\x00",
+            // inactive slot
+            b"\x00",
+            // active upgrade planner, generation 7, item upgrade-planner, no label, no description, no icons
+            b"\x01\x03\x07\x00\x00\x00\x04\x00\x00\x00\x00",
+            // 3 mapping slots of (kind, from, no quality condition, to, no quality),
+            // underground belt to express underground belt, then empty item slot and empty entity slot
+            b"\x03\x00\x03\x00\x00\x02\x00\x00\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00",
+            // book active index 2, skip
+            b"\x02\x00",
+            // inactive slot
+            b"\x00",
+        ].concat();
+
+        let parsed = Parser::new(Reader::new(&original)).parse().unwrap();
+        assert_eq!(parsed.print_count, 2);
+        assert!(parsed.skipped.is_empty(), "{:?}", parsed.skipped);
+        let [(0, Print::BlueprintBook(book))] = parsed.prints.as_slice() else { panic!("not only book") };
+        assert_eq!((book.label.as_ref(), book.print_count, book.active_index), ("book", 3, 2));
+        assert_eq!(book.prints.iter().map(|(index, _)| *index).collect::<Vec<_>>(), [0, 2]);
+
+        let (_, Print::DeconstructionPlan(plan)) = &book.prints[0] else { panic!("not deconstruction plan") };
+        assert!(matches!(plan.entity_filter_mode, FilterMode::Blacklist));
+        assert_eq!(plan.entity_filter_count, 4);
+        assert_eq!(plan.entity_filters.iter().map(|(index, filter)| (*index, filter.name.as_ref())).collect::<Vec<_>>(), [(1, "roboport")]);
+        assert!(matches!(plan.tile_selection_mode, TileSelectionMode::Only));
+        assert_eq!(plan.tile_filter_count, 3);
+        assert_eq!(plan.tile_filters, [(0, "landfill".into())]);
+
+        let (_, Print::UpgradePlan(plan)) = &book.prints[1] else { panic!("not upgrade plan") };
+        assert_eq!(plan.mapping_count, 3);
+        let (0, mapping) = &plan.mappings[0] else { panic!("not first slot") };
+        assert_eq!((mapping.from.as_ref().map(|(name, _)| name.as_ref()), mapping.to.as_ref().map(|(name, _)| name.as_ref())),
+            (Some("underground-belt"), Some("express-underground-belt")));
+        assert!(matches!(plan.mappings[1], (1, UpgradeMapping{ kind: UpgradeKind::Item, from: None, to: None })));
+
+        assert_eq!(serialize(&parsed), original);
+
+        // icons are not understood, the whole file cannot be read
+        let mut icons = original.clone();
+        let offset = icons.windows(7).position(|w| w == b"\x04book\x00\x00").unwrap();
+        icons[offset + 6] = 1;
+        let error = Parser::new(Reader::new(&icons)).parse().unwrap_err();
+        assert_eq!(error.path, "prints[0].icon_count");
+    }
+}
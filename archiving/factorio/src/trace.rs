@@ -0,0 +1,98 @@
+// trace every read of the reader, for reverse engineering the binary format,
+// each record is one read with semantic path like prints[3].entities[12].position.x,
+// render as annotated hex dump for reading or json for other tools
+
+use std::fmt::Write;
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy)]
+pub enum PathSegment {
+    Field(&'static str),
+    Index(&'static str, usize),
+}
+
+pub struct TraceRecord<'a> {
+    pub offset: usize,
+    pub raw: &'a [u8],
+    // formatted decoded value
+    pub value: String,
+    pub path: String,
+}
+
+pub fn format_path(path: &[PathSegment]) -> String {
+    let mut result = String::new();
+    for (index, segment) in path.iter().enumerate() {
+        if index > 0 { result.push('.'); }
+        match segment {
+            PathSegment::Field(name) => result.push_str(name),
+            PathSegment::Index(name, index) => write!(result, "{name}[{index}]").unwrap(),
+        }
+    }
+    result
+}
+
+// bytes per line in hex dump, longer records continue in following lines
+const HEX_DUMP_WIDTH: usize = 16;
+
+// like
+// 00000040  05 72 6f 62 6f 74                                prints[0].label = "robot"
+pub fn render_hex_dump(records: &[TraceRecord]) -> String {
+    let mut result = String::new();
+    for record in records {
+        if record.raw.is_empty() {
+            writeln!(result, "{:08x}  {:width$}  {} = {}", record.offset, "", record.path, record.value, width = HEX_DUMP_WIDTH * 3 - 1).unwrap();
+            continue;
+        }
+        for (line_index, line) in record.raw.chunks(HEX_DUMP_WIDTH).enumerate() {
+            let hex = line.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ");
+            let offset = record.offset + line_index * HEX_DUMP_WIDTH;
+            if line_index == 0 {
+                writeln!(result, "{offset:08x}  {hex:width$}  {} = {}", record.path, record.value, width = HEX_DUMP_WIDTH * 3 - 1).unwrap();
+            } else {
+                writeln!(result, "{offset:08x}  {hex}").unwrap();
+            }
+        }
+    }
+    result
+}
+
+pub fn render_json(records: &[TraceRecord]) -> Value {
+    Value::Array(records.iter().map(|record| json!({
+        "offset": record.offset,
+        "length": record.raw.len(),
+        "raw": record.raw.iter().map(|b| format!("{b:02x}")).collect::<String>(),
+        "value": record.value,
+        "path": record.path,
+    })).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_reader::Reader;
+    use super::*;
+
+    #[test]
+    fn trace_path() -> anyhow::Result<()> {
+        let buffer = b"\x02\x00\x00\x00\x05robot\x01";
+        let mut reader = Reader::with_trace(buffer);
+        reader.enter_index("prints", 3);
+        reader.field("count").read_u32()?;
+        reader.enter_index("entities", 12);
+        reader.field("label").read_str()?;
+        reader.leave();
+        reader.read_bool()?;
+        reader.leave();
+
+        let records = reader.take_trace();
+        let paths = records.iter().map(|r| (r.offset, r.raw.len(), r.path.as_str(), r.value.as_str())).collect::<Vec<_>>();
+        assert_eq!(paths, [
+            (0, 4, "prints[3].count", "2"),
+            (4, 6, "prints[3].entities[12].label", "\"robot\""),
+            (10, 1, "prints[3]", "true"),
+        ]);
+        assert_eq!(render_hex_dump(&records).lines().nth(1),
+            Some("00000004  05 72 6f 62 6f 74                                prints[3].entities[12].label = \"robot\""));
+        assert_eq!(render_json(&records)[1]["raw"], "05726f626f74");
+        Ok(())
+    }
+}